@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use elliptic_curve::{Ecdsa, Signature};
+use rug::{integer::Order, Integer};
+use secp256k1_rust::{hash::create_sha256_from_string, secp256k1::Secp256k1};
+
+fn signed(count: usize) -> Vec<(Secp256k1, Integer, Signature<Integer>, u8)> {
+    (0..count)
+        .map(|i| {
+            let sec256 = Secp256k1::generate_key_pair_from_secret(&format!("secret {}", i));
+            let z = Integer::from_digits(
+                create_sha256_from_string(&format!("message {}", i)).as_slice(),
+                Order::MsfBe,
+            );
+            let k = sec256.deterministic_k(z.clone()).unwrap();
+            let (sig, recovery_id) = sec256.sign_recoverable(z.clone(), k).unwrap();
+            (
+                Secp256k1::new(None, sec256.public_key.clone()),
+                z,
+                sig,
+                recovery_id,
+            )
+        })
+        .collect()
+}
+
+fn batch_verification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify");
+    for count in [8, 64] {
+        let items = signed(count);
+        group.bench_with_input(BenchmarkId::new("sequential", count), &items, |b, items| {
+            b.iter(|| {
+                items
+                    .iter()
+                    .all(|(key, z, sig, _)| key.verify(black_box(z.clone()), sig.clone()))
+            })
+        });
+        let triples: Vec<_> = items
+            .iter()
+            .map(|(key, z, sig, _)| (key.clone(), z.clone(), sig.clone()))
+            .collect();
+        group.bench_with_input(BenchmarkId::new("batch", count), &triples, |b, triples| {
+            b.iter(|| Secp256k1::verify_batch(black_box(triples)).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("batch_recoverable", count),
+            &items,
+            |b, items| b.iter(|| Secp256k1::verify_batch_recoverable(black_box(items)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, batch_verification);
+criterion_main!(benches);
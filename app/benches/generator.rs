@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use elliptic_curve::Ecdsa;
+use rug::Integer;
+use secp256k1_rust::secp256k1::Secp256k1;
+
+fn generator_multiplication(c: &mut Criterion) {
+    let g = Secp256k1::get_g();
+    let k = Integer::from_str_radix(
+        "c28a9f80738f770ac5d4a0f02e8d3d1e4e2a3b2d81d4f5e6a7b8c9d0e1f2a3b4",
+        16,
+    )
+    .unwrap();
+
+    // テーブルの構築は初回のみなので計測から外す
+    Secp256k1::mul_generator(&k);
+
+    let mut group = c.benchmark_group("generator");
+    group.bench_function("point_mul", |b| {
+        b.iter(|| black_box(g.clone()) * black_box(k.clone()))
+    });
+    group.bench_function("mul_generator", |b| {
+        b.iter(|| Secp256k1::mul_generator(black_box(&k)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, generator_multiplication);
+criterion_main!(benches);
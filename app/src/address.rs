@@ -0,0 +1,260 @@
+use elliptic_curve::Ecdsa;
+
+use crate::{
+    base58::{decode_base58_checksum, encode_base58_checksum},
+    bech32::{convert_bits, decode_bech32, encode_bech32, Variant},
+    hash::{create_hash160, create_sha256},
+    secp256k1::Secp256k1,
+    Error, Network,
+};
+
+/// hash160 のバイト数
+const HASH160_LENGTH: usize = 20;
+
+impl Secp256k1 {
+    /// 公開鍵のSEC形式から P2PKH アドレスを求める
+    pub fn address(&self, compressed: bool, network: Network) -> String {
+        let hash160: [u8; HASH160_LENGTH] =
+            create_hash160(&self.sec(compressed)).try_into().unwrap();
+        encode_p2pkh_address(&hash160, network)
+    }
+
+    /// 圧縮公開鍵の hash160 を witness プログラムとする P2WPKH アドレスを求める
+    pub fn p2wpkh_address(&self, network: Network) -> String {
+        encode_segwit_address(0, &create_hash160(&self.sec(true)), network).unwrap()
+    }
+}
+
+/// バージョンバイトと hash160 から P2PKH アドレスを作る
+pub fn encode_p2pkh_address(hash160: &[u8; HASH160_LENGTH], network: Network) -> String {
+    encode_base58_checksum(&[&[network.p2pkh_prefix()], &hash160[..]].concat())
+}
+
+/// P2PKH アドレスのチェックサムとバージョンバイトを検証し、ネットワークと hash160 を返す
+pub fn decode_p2pkh_address(address: &str) -> Result<(Network, [u8; HASH160_LENGTH]), Error> {
+    let payload = decode_base58_checksum(address)?;
+    if payload.len() != HASH160_LENGTH + 1 {
+        return Err(Error::InvalidAddressLength(payload.len()));
+    }
+
+    let network =
+        Network::from_p2pkh_prefix(payload[0]).ok_or(Error::InvalidAddressVersion(payload[0]))?;
+    Ok((network, payload[1..].try_into().unwrap()))
+}
+
+/// witness スクリプトの SHA256 を witness プログラムとする P2WSH アドレスを求める
+pub fn p2wsh_address(witness_script: &[u8], network: Network) -> String {
+    encode_segwit_address(0, &create_sha256(witness_script), network).unwrap()
+}
+
+/// x座標のみの出力鍵を witness プログラムとする P2TR アドレスを求める
+pub fn p2tr_address(output_key: &[u8; 32], network: Network) -> String {
+    encode_segwit_address(1, output_key, network).unwrap()
+}
+
+/// witness バージョンとプログラムから SegWit アドレスを作る
+///
+/// バージョン0は Bech32、1以上は Bech32m でエンコードする
+pub fn encode_segwit_address(
+    version: u8,
+    program: &[u8],
+    network: Network,
+) -> Result<String, Error> {
+    let variant = validate_witness_program(version, program)?;
+    let data = [vec![version], convert_bits(program, 8, 5, true)?].concat();
+    Ok(encode_bech32(network.bech32_hrp(), &data, variant))
+}
+
+/// SegWit アドレスをデコードし、`(ネットワーク, witness バージョン, プログラム)` を返す
+pub fn decode_segwit_address(address: &str) -> Result<(Network, u8, Vec<u8>), Error> {
+    let (hrp, data, variant) = decode_bech32(address)?;
+    let network = Network::from_bech32_hrp(&hrp).ok_or(Error::InvalidSegwitHrp(hrp))?;
+
+    let (&version, program) = data
+        .split_first()
+        .ok_or(Error::InvalidBech32("missing witness version"))?;
+    let program = convert_bits(program, 5, 8, false)?;
+    if validate_witness_program(version, &program)? != variant {
+        return Err(Error::InvalidWitnessVersion(version));
+    }
+    Ok((network, version, program))
+}
+
+/// BIP141 の制約を確認し、そのバージョンで使うべきチェックサムの種類を返す
+fn validate_witness_program(version: u8, program: &[u8]) -> Result<Variant, Error> {
+    if version > 16 {
+        return Err(Error::InvalidWitnessVersion(version));
+    }
+    if !(2..=40).contains(&program.len()) {
+        return Err(Error::InvalidWitnessProgramLength(program.len()));
+    }
+    if version == 0 {
+        // v0 は P2WPKH (20バイト) か P2WSH (32バイト) のみ
+        if program.len() != 20 && program.len() != 32 {
+            return Err(Error::InvalidWitnessProgramLength(program.len()));
+        }
+        return Ok(Variant::Bech32);
+    }
+    Ok(Variant::Bech32m)
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::{ops::Pow, Integer};
+
+    use super::*;
+
+    fn key(secret: Integer) -> Secp256k1 {
+        let public_key = Secp256k1::mul_generator(&secret);
+        Secp256k1::new(Some(secret), public_key)
+    }
+
+    #[test]
+    fn test_address() {
+        let cases = [
+            (
+                Integer::from(5002),
+                false,
+                Network::Testnet,
+                "mmTPbXQFxboEtNRkwfh6K51jvdtHLxGeMA",
+            ),
+            (
+                Integer::from(2020).pow(5),
+                true,
+                Network::Testnet,
+                "mopVkxp8UhXqRYbCYJsbeE1h1fiF64jcoH",
+            ),
+            (
+                Integer::from(0x12345deadbeefu64),
+                true,
+                Network::Mainnet,
+                "1F1Pn2y6pDb68E5nYJJeba4TLg2U7B6KF1",
+            ),
+        ];
+        for (secret, compressed, network, address) in cases {
+            let sec256 = key(secret);
+            assert_eq!(sec256.address(compressed, network), address);
+
+            let (decoded_network, hash160) = decode_p2pkh_address(address).unwrap();
+            assert_eq!(decoded_network, network);
+            assert_eq!(hash160.to_vec(), create_hash160(&sec256.sec(compressed)));
+        }
+    }
+
+    #[test]
+    fn test_decode_p2pkh_address_invalid() {
+        assert_eq!(
+            decode_p2pkh_address("1F1Pn2y6pDb68E5nYJJeba4TLg2U7B6KF2"),
+            Err(Error::InvalidBase58Checksum)
+        );
+
+        // P2SH のバージョンバイト (0x05)
+        let p2sh = encode_base58_checksum(&[[0x05].as_slice(), &[0u8; 20]].concat());
+        assert_eq!(
+            decode_p2pkh_address(&p2sh),
+            Err(Error::InvalidAddressVersion(0x05))
+        );
+
+        let short = encode_base58_checksum(&[0u8; 20]);
+        assert_eq!(
+            decode_p2pkh_address(&short),
+            Err(Error::InvalidAddressLength(20))
+        );
+    }
+
+    #[test]
+    fn test_segwit_address() {
+        let cases = [
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                Network::Mainnet,
+                0,
+                "751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                Network::Testnet,
+                0,
+                "1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                Network::Mainnet,
+                1,
+                "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ];
+        for (address, network, version, program) in cases {
+            let program = hex::decode(program).unwrap();
+            assert_eq!(
+                decode_segwit_address(address).unwrap(),
+                (network, version, program.clone())
+            );
+            assert_eq!(
+                encode_segwit_address(version, &program, network).unwrap(),
+                address.to_ascii_lowercase()
+            );
+        }
+
+        // 秘密鍵1の公開鍵はGなので、P2WPKH と P2TR は上のテストベクタと一致する
+        let sec256 = key(Integer::from(1));
+        assert_eq!(
+            sec256.p2wpkh_address(Network::Mainnet),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            p2tr_address(&sec256.xonly_public_key(), Network::Mainnet),
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        );
+
+        // 1-of-1 マルチシグ (BIP173 のP2WSHの例) の witness スクリプト
+        let witness_script =
+            hex::decode("210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ac")
+                .unwrap();
+        assert_eq!(
+            p2wsh_address(&witness_script, Network::Testnet),
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"
+        );
+    }
+
+    #[test]
+    fn test_decode_segwit_address_invalid() {
+        let program = [0x75u8; 20];
+
+        // バージョンとチェックサムの種類が一致しない
+        let data = [vec![0], convert_bits(&program, 8, 5, true).unwrap()].concat();
+        let v0_bech32m = encode_bech32("bc", &data, Variant::Bech32m);
+        assert_eq!(
+            decode_segwit_address(&v0_bech32m),
+            Err(Error::InvalidWitnessVersion(0))
+        );
+        let data = [vec![1], convert_bits(&program, 8, 5, true).unwrap()].concat();
+        let v1_bech32 = encode_bech32("bc", &data, Variant::Bech32);
+        assert_eq!(
+            decode_segwit_address(&v1_bech32),
+            Err(Error::InvalidWitnessVersion(1))
+        );
+
+        let data = [vec![17], convert_bits(&program, 8, 5, true).unwrap()].concat();
+        assert_eq!(
+            decode_segwit_address(&encode_bech32("bc", &data, Variant::Bech32m)),
+            Err(Error::InvalidWitnessVersion(17))
+        );
+        assert_eq!(
+            encode_segwit_address(0, &[0u8; 21], Network::Mainnet),
+            Err(Error::InvalidWitnessProgramLength(21))
+        );
+        assert_eq!(
+            encode_segwit_address(1, &[0u8; 41], Network::Mainnet),
+            Err(Error::InvalidWitnessProgramLength(41))
+        );
+        assert_eq!(
+            decode_segwit_address(&encode_bech32("ltc", &data, Variant::Bech32m)),
+            Err(Error::InvalidSegwitHrp("ltc".to_string()))
+        );
+        assert_eq!(
+            decode_segwit_address("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(Error::InvalidBech32("invalid checksum"))
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use rug::{integer::Order, Integer};
+use zeroize::Zeroizing;
+
+use crate::{hash::create_hash256, secret::SecretInteger, Error};
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Base58Check のチェックサムのバイト数
+const CHECKSUM_LENGTH: usize = 4;
+
+pub fn encode_base58(bytes: &[u8]) -> String {
+    // 先頭の0x00は '1' で表す
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // WIFでは秘密鍵を含むので、途中の整数は drop 時に消去する
+    let mut num = SecretInteger::new(Integer::from_digits(bytes, Order::MsfBe));
+
+    // 58進の桁数は 256進の桁数の log(256)/log(58) < 1.37 倍なので、伸長による再確保は起きない
+    let mut result = Vec::with_capacity(bytes.len() * 137 / 100 + 1);
+    while *num > 0 {
+        let rem = num.mod_u(58);
+        *num /= 58;
+        result.push(BASE58_ALPHABET[rem as usize]);
+    }
+    result.extend(std::iter::repeat_n(b'1', zeros));
+    result.reverse();
+    String::from_utf8(result).unwrap()
+}
+
+pub fn decode_base58(s: &str) -> Result<Vec<u8>, Error> {
+    let zeros = s.chars().take_while(|&c| c == '1').count();
+
+    // 1文字は6ビット未満なので、計算途中で再確保されて古いリムが残ることはない
+    let mut num = SecretInteger::new(Integer::with_capacity(s.len() * 6));
+    for c in s.chars() {
+        let digit = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or(Error::InvalidBase58Character(c))?;
+        *num *= 58;
+        *num += digit as u32;
+    }
+
+    let mut bytes = vec![0u8; zeros + num.significant_digits::<u8>()];
+    num.write_digits(&mut bytes[zeros..], Order::MsfBe);
+    Ok(bytes)
+}
+
+/// 末尾に hash256 の先頭4バイトをチェックサムとして付けてBase58にする
+pub fn encode_base58_checksum(bytes: &[u8]) -> String {
+    let checksum = create_hash256(bytes);
+    let data = Zeroizing::new([bytes, &checksum[..CHECKSUM_LENGTH]].concat());
+    encode_base58(&data)
+}
+
+/// Base58Check をデコードし、チェックサムを検証して取り除いたペイロードを返す
+pub fn decode_base58_checksum(s: &str) -> Result<Vec<u8>, Error> {
+    let mut bytes = Zeroizing::new(decode_base58(s)?);
+    if bytes.len() < CHECKSUM_LENGTH {
+        return Err(Error::InvalidBase58Checksum);
+    }
+
+    let payload_length = bytes.len() - CHECKSUM_LENGTH;
+    let (payload, checksum) = bytes.split_at(payload_length);
+    if create_hash256(payload)[..CHECKSUM_LENGTH] != *checksum {
+        return Err(Error::InvalidBase58Checksum);
+    }
+    // 複製せずにチェックサムを切り落として返す
+    bytes.truncate(payload_length);
+    Ok(std::mem::take(&mut *bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base58() {
+        let cases = [
+            (
+                "7c076ff316692a3d7eb3c3bb0f8b1488cf72e1afcd929e29307032997a838a3d",
+                "9MA8fRQrT4u8Zj8ZRd6MAiiyaxb2Y1CMpvVkHQu5hVM6",
+            ),
+            (
+                "eff69ef2b1bd93a66ed5219add4fb51e11a840f404876325a1e8ffe0529a2c",
+                "4fE3H2E6XMp4SsxtwinF7w9a34ooUrwWe4WsW1458Pd",
+            ),
+            (
+                "c7207fee197d27c618aea621406f6bf5ef6fca38681d82b2f06fddbdce6feab6",
+                "EQJsjkd6JaGwxrjEhfeqPenqHwrBmPQZjJGNSCHBkcF7",
+            ),
+            ("", ""),
+            ("0000", "11"),
+            ("000001", "112"),
+        ];
+        for (hex_str, base58) in cases {
+            let bytes = hex::decode(hex_str).unwrap();
+            assert_eq!(encode_base58(&bytes), base58);
+            assert_eq!(decode_base58(base58).unwrap(), bytes);
+        }
+
+        // 0, O, I, l は使わない
+        assert_eq!(
+            decode_base58("1O1"),
+            Err(Error::InvalidBase58Character('O'))
+        );
+        assert_eq!(
+            decode_base58("abc0"),
+            Err(Error::InvalidBase58Character('0'))
+        );
+    }
+
+    #[test]
+    fn test_base58_checksum() {
+        let payload = hex::decode("00f54a5851e9372b87810a8e60cdd2e7cfd80b6e31").unwrap();
+        let address = "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs";
+
+        assert_eq!(encode_base58_checksum(&payload), address);
+        assert_eq!(decode_base58_checksum(address).unwrap(), payload);
+
+        // 1文字変えるとチェックサムが合わない
+        assert_eq!(
+            decode_base58_checksum("1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAt"),
+            Err(Error::InvalidBase58Checksum)
+        );
+        assert_eq!(
+            decode_base58_checksum("111"),
+            Err(Error::InvalidBase58Checksum)
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use elliptic_curve::{multi_mul_wnaf, Ecdsa, JacobianPoint, Point, Signature};
+use field_element::FieldElement;
+use rug::{integer::Order, Integer};
+
+use crate::{
+    glv::{endomorphism, lambda},
+    hash::create_sha256,
+    secp256k1::{to_32_bytes, Secp256k1},
+    Error,
+};
+
+/// Rの符号を総当たりする署名の数。1つのまとまりごとに 2^BATCH_CHUNK_SIZE 通りを調べる
+const BATCH_CHUNK_SIZE: usize = 8;
+
+/// 補正項 2 a_i R_i の計算に使うwNAFの幅
+const WNAF_WIDTH: u32 = 4;
+
+/// ECDSA署名のバッチ検証
+impl Secp256k1 {
+    /// `(公開鍵, z, 署名)` の組をまとめて検証し、不正な署名があればその位置を返す
+    ///
+    /// 各署名の r から y が偶数の点Rを復元し、乱数係数 a_i で
+    /// (Σ a_i u_i) G + Σ a_i v_i P_i - Σ a_i R_i
+    /// を1回のマルチスカラー倍算で求める。実際のRのyが奇数の署名では 2 a_i R_i だけずれるので、
+    /// BATCH_CHUNK_SIZE 個ずつのまとまりで、ずれの和が打ち消し合う組み合わせをGrayコード順に探す。
+    /// 式が成り立たないまとまりは個別に検証して不正な署名を探す
+    pub fn verify_batch(items: &[(Secp256k1, Integer, Signature<Integer>)]) -> Result<(), Error> {
+        let seed = batch_seed(items.iter().map(|(key, z, sig)| (key, z, sig, None)))?;
+        for (chunk_index, chunk) in items.chunks(BATCH_CHUNK_SIZE).enumerate() {
+            let offset = chunk_index * BATCH_CHUNK_SIZE;
+            if chunk_equation_holds(chunk, &seed, offset) {
+                continue;
+            }
+            for (i, (public_key, z, sig)) in chunk.iter().enumerate() {
+                if !public_key.verify(z.clone(), sig.clone()) {
+                    return Err(Error::BatchVerification { index: offset + i });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `(公開鍵, z, 署名, リカバリーID)` の組をまとめて検証し、不正な署名があればその位置を返す
+    ///
+    /// リカバリーID (`sign_recoverable` が返すもの) から各署名の点Rを復元し、乱数係数 a_i で
+    /// (Σ a_i u_i) G + Σ a_i v_i P_i - Σ a_i R_i = O
+    /// を1回のマルチスカラー倍算で確認する。Rの符号を探さないぶん `verify_batch` より速い。
+    /// 式が成り立たなければ個別に検証して不正な署名を探すので、
+    /// リカバリーIDが誤っているだけの署名は遅くなるが有効と判定される
+    pub fn verify_batch_recoverable(
+        items: &[(Secp256k1, Integer, Signature<Integer>, u8)],
+    ) -> Result<(), Error> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let seed = batch_seed(
+            items
+                .iter()
+                .map(|(key, z, sig, recovery_id)| (key, z, sig, Some(*recovery_id))),
+        )?;
+        if recoverable_equation_holds(items, &seed) {
+            return Ok(());
+        }
+
+        // どの署名が不正かを個別に検証して特定する
+        for (index, (public_key, z, sig, _)) in items.iter().enumerate() {
+            if !public_key.verify(z.clone(), sig.clone()) {
+                return Err(Error::BatchVerification { index });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 乱数係数の種として全入力のハッシュを求める
+///
+/// 攻撃者が係数を予測して不正な署名同士を打ち消せないよう、入力全体に依存させる
+fn batch_seed<'a>(
+    items: impl Iterator<
+        Item = (
+            &'a Secp256k1,
+            &'a Integer,
+            &'a Signature<Integer>,
+            Option<u8>,
+        ),
+    >,
+) -> Result<Vec<u8>, Error> {
+    let n = Secp256k1::get_n();
+    let mut preimage = vec![];
+    for (index, (public_key, z, sig, recovery_id)) in items.enumerate() {
+        if public_key.public_key.x.is_none()
+            || sig.r < 1
+            || sig.r >= n
+            || sig.s < 1
+            || sig.s >= n
+            || recovery_id.is_some_and(|id| id > 3)
+        {
+            return Err(Error::BatchVerification { index });
+        }
+        preimage.extend(public_key.sec(true));
+        preimage.extend(to_32_bytes(&(z.clone() % &n)));
+        preimage.extend(to_32_bytes(&sig.r));
+        preimage.extend(to_32_bytes(&sig.s));
+        preimage.extend(recovery_id);
+    }
+    Ok(create_sha256(&preimage))
+}
+
+/// 乱数係数 a_i = b_i + c_i λ (mod n) の64bitの b_i, c_i を求める (a_0 = 1)
+///
+/// a_i は128bitの乱数を持つが、a_i R = b_i R + c_i φ(R) を64回の二倍算で求められる
+fn randomizer(seed: &[u8], index: usize) -> (Integer, Integer) {
+    if index == 0 {
+        return (Integer::from(1), Integer::new());
+    }
+    let hash = create_sha256(&[seed, &(index as u64).to_be_bytes()].concat());
+    (
+        Integer::from_digits(&hash[..8], Order::MsfBe),
+        Integer::from_digits(&hash[8..16], Order::MsfBe),
+    )
+}
+
+/// 多点スカラー倍算の一つの項 (点, スカラー)
+type Term = (Point<FieldElement<Integer>, Integer>, Integer);
+
+/// 検証式の (G, Σ a_i u_i) と (P_i, a_i v_i) の項を作る
+///
+/// `offset` はまとまりの先頭の位置で、乱数係数を入力全体で通し番号にするために使う
+fn linear_terms<'a>(
+    items: impl ExactSizeIterator<Item = (&'a Secp256k1, &'a Integer, &'a Signature<Integer>)>,
+    seed: &[u8],
+    offset: usize,
+) -> (Vec<Term>, Vec<Integer>) {
+    let n = Secp256k1::get_n();
+    let items: Vec<_> = items.collect();
+    let s_invs = batch_invert(items.iter().map(|(_, _, sig)| &sig.s), &n);
+
+    let mut g_scalar = Integer::from(0);
+    let mut terms = Vec::with_capacity(2 * items.len() + 1);
+    let mut randomizers = Vec::with_capacity(items.len());
+    for (i, ((public_key, z, sig), s_inv)) in items.into_iter().zip(s_invs).enumerate() {
+        let (b, c) = randomizer(seed, offset + i);
+        let a = (b + c * lambda()) % &n;
+        let u = z.clone() * &s_inv % &n;
+        let v = sig.r.clone() * s_inv % &n;
+
+        g_scalar = (g_scalar + a.clone() * u) % &n;
+        terms.push((public_key.public_key.clone(), a.clone() * v % &n));
+        randomizers.push(a);
+    }
+    terms.push((Secp256k1::get_g(), g_scalar));
+    (terms, randomizers)
+}
+
+/// リカバリーIDで復元したRを使い、全署名の検証式を1回のマルチスカラー倍算で確認する
+///
+/// Rが復元できない署名があれば、リカバリーIDの誤りかもしれないので偽を返して個別の検証に任せる
+fn recoverable_equation_holds(
+    items: &[(Secp256k1, Integer, Signature<Integer>, u8)],
+    seed: &[u8],
+) -> bool {
+    let n = Secp256k1::get_n();
+    let (mut terms, randomizers) =
+        linear_terms(items.iter().map(|(key, z, sig, _)| (key, z, sig)), seed, 0);
+    for ((_, _, sig, recovery_id), a) in items.iter().zip(randomizers) {
+        // リカバリーIDの上位bitはx(R)がn以上か、下位bitはyの偶奇を表す
+        let mut r_x = sig.r.clone();
+        if recovery_id & 2 != 0 {
+            r_x += &n;
+        }
+        let Ok(big_r) = Secp256k1::try_create_field_element(r_x)
+            .and_then(|r_x| Secp256k1::lift_x(r_x, recovery_id & 1 == 1))
+        else {
+            return false;
+        };
+        terms.push((big_r, n.clone() - a));
+    }
+
+    Secp256k1::linear_combination(&terms).x.is_none()
+}
+
+/// Rの符号が分からない署名のまとまりで、検証式が成り立つ符号の組み合わせがあるか調べる
+///
+/// x(R) = r でRが復元できない署名があれば偽を返す (x(R) が n 以上の署名は個別の検証で扱う)
+fn chunk_equation_holds(
+    items: &[(Secp256k1, Integer, Signature<Integer>)],
+    seed: &[u8],
+    offset: usize,
+) -> bool {
+    let (terms, _) = linear_terms(
+        items.iter().map(|(key, z, sig)| (key, z, sig)),
+        seed,
+        offset,
+    );
+    let mut acc = JacobianPoint::from(Secp256k1::to_fe256_point(Secp256k1::linear_combination(
+        &terms,
+    )));
+
+    // a_i R_i は符号の補正にも使うので、マルチスカラー倍算に入れずに個別に求めて引く
+    let mut corrections = Vec::with_capacity(items.len());
+    for (i, (_, _, sig)) in items.iter().enumerate() {
+        let Ok(big_r) = Secp256k1::try_create_field_element(sig.r.clone())
+            .and_then(|r_x| Secp256k1::lift_x(r_x, false))
+        else {
+            return false;
+        };
+        let (b, c) = randomizer(seed, offset + i);
+        let r256 = Secp256k1::to_fe256_point(big_r);
+        let mut a_r = multi_mul_wnaf(&[(endomorphism(&r256), c), (r256, b)], WNAF_WIDTH)
+            .expect("terms is not empty");
+        a_r.y = -a_r.y;
+        acc = acc + a_r.clone();
+
+        // Rのyが実際は奇数なら 2 a_i R_i だけ足りない
+        let negated = a_r.double();
+        let mut correction = negated.clone();
+        correction.y = -correction.y;
+        corrections.push((correction, negated));
+    }
+
+    if acc.is_infinity() {
+        return true;
+    }
+    // Grayコード順に1つずつ補正を加えたり外したりして、全ての組み合わせを1回の加算ずつで試す
+    for step in 1u32..(1 << items.len()) {
+        let bit = step.trailing_zeros() as usize;
+        let gray = step ^ (step >> 1);
+        let (correction, negated) = &corrections[bit];
+        acc = if gray & (1 << bit) != 0 {
+            acc + correction.clone()
+        } else {
+            acc + negated.clone()
+        };
+        if acc.is_infinity() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Montgomeryのトリックで、1回の逆元計算と 3(k-1) 回の乗算で k 個の逆元を mod n で求める
+fn batch_invert<'a>(values: impl Iterator<Item = &'a Integer>, n: &Integer) -> Vec<Integer> {
+    // prefix[i] = values[0] * ... * values[i]
+    let values: Vec<_> = values.collect();
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = Integer::from(1);
+    for value in &values {
+        acc = acc * *value % n;
+        prefix.push(acc.clone());
+    }
+
+    let mut inv = acc.invert(n).expect("values are in range 1 to n - 1");
+    let mut result = vec![Integer::new(); values.len()];
+    for i in (0..values.len()).rev() {
+        result[i] = if i == 0 {
+            inv.clone()
+        } else {
+            inv.clone() * &prefix[i - 1] % n
+        };
+        inv = inv * values[i] % n;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::integer::Order;
+
+    use super::*;
+    use crate::hash::create_sha256_from_string;
+
+    type Recoverable = (Secp256k1, Integer, Signature<Integer>, u8);
+
+    fn signed(secret: &str, message: &str) -> Recoverable {
+        let sec256 = Secp256k1::generate_key_pair_from_secret(secret);
+        let z = Integer::from_digits(create_sha256_from_string(message).as_slice(), Order::MsfBe);
+        let k = sec256.deterministic_k(z.clone()).unwrap();
+        let (sig, recovery_id) = sec256.sign_recoverable(z.clone(), k).unwrap();
+        (
+            Secp256k1::new(None, sec256.public_key.clone()),
+            z,
+            sig,
+            recovery_id,
+        )
+    }
+
+    fn items(count: usize) -> Vec<Recoverable> {
+        (0..count)
+            .map(|i| signed(&format!("secret {}", i), &format!("message {}", i)))
+            .collect()
+    }
+
+    fn triples(items: &[Recoverable]) -> Vec<(Secp256k1, Integer, Signature<Integer>)> {
+        items
+            .iter()
+            .map(|(key, z, sig, _)| (key.clone(), z.clone(), sig.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        // Strauss法と Pippenger法の両方の点の数、端数のまとまりで確認する
+        for count in [1, 10, 40] {
+            let items = items(count);
+            let triples = triples(&items);
+            assert_eq!(Secp256k1::verify_batch(&triples), Ok(()));
+            assert_eq!(Secp256k1::verify_batch_recoverable(&items), Ok(()));
+
+            // 個別検証へのフォールバックなしで全体が検証できる
+            let seed = batch_seed(triples.iter().map(|(key, z, sig)| (key, z, sig, None))).unwrap();
+            for (i, chunk) in triples.chunks(BATCH_CHUNK_SIZE).enumerate() {
+                assert!(chunk_equation_holds(chunk, &seed, i * BATCH_CHUNK_SIZE));
+            }
+            let seed = batch_seed(
+                items
+                    .iter()
+                    .map(|(key, z, sig, recovery_id)| (key, z, sig, Some(*recovery_id))),
+            )
+            .unwrap();
+            assert!(recoverable_equation_holds(&items, &seed));
+        }
+        assert_eq!(Secp256k1::verify_batch(&[]), Ok(()));
+        assert_eq!(Secp256k1::verify_batch_recoverable(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_batch_odd_and_even_r() {
+        // Rのyの偶奇が混ざっていても符号の組み合わせが見つかる
+        let items = items(BATCH_CHUNK_SIZE);
+        let parities: Vec<_> = items.iter().map(|item| item.3 & 1).collect();
+        assert!(parities.contains(&0) && parities.contains(&1));
+        let triples = triples(&items);
+        assert!(chunk_equation_holds(
+            &triples,
+            &batch_seed(triples.iter().map(|(key, z, sig)| (key, z, sig, None))).unwrap(),
+            0
+        ));
+    }
+
+    #[test]
+    fn test_verify_batch_wrong_recovery_id() {
+        // yの偶奇 (bit 0) と x(R) が n 以上か (bit 1) のどちらが誤っていても有効と判定される
+        for flip in [1, 2, 3] {
+            let mut items = items(5);
+            items[2].3 ^= flip;
+
+            let seed = batch_seed(
+                items
+                    .iter()
+                    .map(|(key, z, sig, recovery_id)| (key, z, sig, Some(*recovery_id))),
+            )
+            .unwrap();
+            assert!(!recoverable_equation_holds(&items, &seed));
+            assert_eq!(Secp256k1::verify_batch_recoverable(&items), Ok(()));
+
+            let mut single = vec![items[0].clone()];
+            single[0].3 ^= flip;
+            assert_eq!(Secp256k1::verify_batch_recoverable(&single), Ok(()));
+        }
+
+        let mut items = items(5);
+        items[2].3 = 4;
+        assert_eq!(
+            Secp256k1::verify_batch_recoverable(&items),
+            Err(Error::BatchVerification { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_verify_batch_invalid() {
+        let mut items = items(20);
+
+        // 別のメッセージに対する署名 (2つ目のまとまりの中)
+        items[9].1 += 1;
+        assert_eq!(
+            Secp256k1::verify_batch(&triples(&items)),
+            Err(Error::BatchVerification { index: 9 })
+        );
+        assert_eq!(
+            Secp256k1::verify_batch_recoverable(&items),
+            Err(Error::BatchVerification { index: 9 })
+        );
+
+        // 範囲外のs
+        items[3].2.s = Secp256k1::get_n();
+        assert_eq!(
+            Secp256k1::verify_batch(&triples(&items)),
+            Err(Error::BatchVerification { index: 3 })
+        );
+        assert_eq!(
+            Secp256k1::verify_batch_recoverable(&items),
+            Err(Error::BatchVerification { index: 3 })
+        );
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let n = Secp256k1::get_n();
+        let values: Vec<_> = [1, 2, 3, 12345].into_iter().map(Integer::from).collect();
+        let invs = batch_invert(values.iter(), &n);
+        for (value, inv) in values.iter().zip(invs) {
+            assert_eq!(value.clone() * inv % &n, 1);
+        }
+    }
+}
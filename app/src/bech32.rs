@@ -0,0 +1,214 @@
+use crate::Error;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// BIP173 の生成多項式
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// チェックサムの文字数
+const CHECKSUM_LENGTH: usize = 6;
+
+/// 文字列全体の最大長
+const MAX_LENGTH: usize = 90;
+
+/// チェックサムの定数で区別される Bech32 (BIP173) と Bech32m (BIP350)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk = 1u32;
+    for &value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 0x1f));
+    values
+}
+
+/// 5bit の値の列 `data` を人間が読める部分 `hrp` と合わせてエンコードする
+pub fn encode_bech32(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let values = [hrp_expand(hrp), data.to_vec(), vec![0; CHECKSUM_LENGTH]].concat();
+    let checksum = polymod(&values) ^ variant.constant();
+
+    let mut result = format!("{}1", hrp);
+    for &value in data {
+        result.push(CHARSET[value as usize] as char);
+    }
+    for i in 0..CHECKSUM_LENGTH {
+        let value = (checksum >> (5 * (CHECKSUM_LENGTH - 1 - i))) & 0x1f;
+        result.push(CHARSET[value as usize] as char);
+    }
+    result
+}
+
+/// チェックサムを検証し、`(hrp, 5bit の値の列, 種類)` を返す
+pub fn decode_bech32(s: &str) -> Result<(String, Vec<u8>, Variant), Error> {
+    if s.len() > MAX_LENGTH {
+        return Err(Error::InvalidBech32("string too long"));
+    }
+    if s.bytes().any(|b| !(33..=126).contains(&b)) {
+        return Err(Error::InvalidBech32("invalid character"));
+    }
+    if s.bytes().any(|b| b.is_ascii_lowercase()) && s.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(Error::InvalidBech32("mixed case"));
+    }
+    let s = s.to_ascii_lowercase();
+
+    let separator = s
+        .rfind('1')
+        .ok_or(Error::InvalidBech32("missing separator"))?;
+    if separator == 0 {
+        return Err(Error::InvalidBech32("empty human-readable part"));
+    }
+    if separator + 1 + CHECKSUM_LENGTH > s.len() {
+        return Err(Error::InvalidBech32("data part too short"));
+    }
+
+    let hrp = &s[..separator];
+    let data = s[separator + 1..]
+        .bytes()
+        .map(|b| CHARSET.iter().position(|&c| c == b).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(Error::InvalidBech32("invalid data character"))?;
+
+    let variant = match polymod(&[hrp_expand(hrp), data.clone()].concat()) {
+        c if c == Variant::Bech32.constant() => Variant::Bech32,
+        c if c == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return Err(Error::InvalidBech32("invalid checksum")),
+    };
+    Ok((
+        hrp.to_string(),
+        data[..data.len() - CHECKSUM_LENGTH].to_vec(),
+        variant,
+    ))
+}
+
+/// `from` bit 単位の値の列を `to` bit 単位に詰め直す
+///
+/// `pad` が false の場合、余ったbitが `from` bit 以上あるか0でなければエラーにする
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Error> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let max = (1u32 << to) - 1;
+    let mut result = vec![];
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err(Error::InvalidBech32("value out of range"));
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err(Error::InvalidBech32("invalid padding"));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bech32() {
+        // BIP173 / BIP350 の有効な文字列
+        let valid = [
+            ("A12UEL5L", Variant::Bech32),
+            ("a12uel5l", Variant::Bech32),
+            (
+                "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+                Variant::Bech32,
+            ),
+            (
+                "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+                Variant::Bech32,
+            ),
+            ("A1LQFN3A", Variant::Bech32m),
+            ("a1lqfn3a", Variant::Bech32m),
+            (
+                "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+                Variant::Bech32m,
+            ),
+        ];
+        for (s, variant) in valid {
+            let (hrp, data, decoded_variant) = decode_bech32(s).unwrap();
+            assert_eq!(decoded_variant, variant);
+            assert_eq!(encode_bech32(&hrp, &data, variant), s.to_ascii_lowercase());
+        }
+
+        assert_eq!(
+            decode_bech32("A12uEL5L"),
+            Err(Error::InvalidBech32("mixed case"))
+        );
+        assert_eq!(
+            decode_bech32("pzry9x0s0muk"),
+            Err(Error::InvalidBech32("missing separator"))
+        );
+        assert_eq!(
+            decode_bech32("1pzry9x0s0muk"),
+            Err(Error::InvalidBech32("empty human-readable part"))
+        );
+        assert_eq!(
+            decode_bech32("li1dgmt3"),
+            Err(Error::InvalidBech32("data part too short"))
+        );
+        assert_eq!(
+            decode_bech32("x1b4n0q5v"),
+            Err(Error::InvalidBech32("invalid data character"))
+        );
+        assert_eq!(
+            decode_bech32("a12uel5m"),
+            Err(Error::InvalidBech32("invalid checksum"))
+        );
+    }
+
+    #[test]
+    fn test_convert_bits() {
+        let bytes = hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let values = convert_bits(&bytes, 8, 5, true).unwrap();
+        assert_eq!(values.len(), 32);
+        assert_eq!(convert_bits(&values, 5, 8, false).unwrap(), bytes);
+
+        // 余りのbitが0でない
+        assert_eq!(
+            convert_bits(&[0x1f], 5, 8, false),
+            Err(Error::InvalidBech32("invalid padding"))
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// 秘密鍵が設定されていない
+    MissingPrivateKey,
+    /// 秘密鍵が1からn-1の範囲外
+    InvalidPrivateKey,
+    /// 公開鍵が無限遠点または曲線上にない
+    InvalidPublicKey,
+    /// tweak が範囲外、または適用した結果が0 (無限遠点) になる
+    InvalidTweak,
+    /// SEC形式のバイト列の長さが不正
+    InvalidSecLength { expected: usize, actual: usize },
+    /// SEC形式のプレフィックスが不正
+    InvalidSecPrefix(u8),
+    /// リカバリーIDが0から3の範囲外
+    InvalidRecoveryId(u8),
+    /// 署名が不正
+    InvalidSignature,
+    /// nonce が1からn-1の範囲外、またはそのnonceでは r か s が0になる
+    InvalidNonce,
+    /// バッチ検証で `index` 番目の署名が不正
+    BatchVerification { index: usize },
+    /// Base58で使えない文字
+    InvalidBase58Character(char),
+    /// Base58Checkのチェックサムが一致しない
+    InvalidBase58Checksum,
+    /// アドレスのバージョンバイトが不正
+    InvalidAddressVersion(u8),
+    /// アドレスのペイロードの長さが不正
+    InvalidAddressLength(usize),
+    /// WIFのプレフィックスが不正
+    InvalidWifPrefix(u8),
+    /// WIFのペイロードの長さが不正
+    InvalidWifLength(usize),
+    /// WIFの圧縮フラグが0x01でない
+    InvalidWifCompressionFlag(u8),
+    /// Bech32/Bech32m の文字列が不正
+    InvalidBech32(&'static str),
+    /// SegWitアドレスの人間が読める部分がどのネットワークとも一致しない
+    InvalidSegwitHrp(String),
+    /// SegWitアドレスのwitnessバージョンが不正、またはチェックサムの種類と一致しない
+    InvalidWitnessVersion(u8),
+    /// witnessプログラムの長さが不正
+    InvalidWitnessProgramLength(usize),
+    /// トランザクションのバイト列が途中で終わっている
+    UnexpectedEndOfData,
+    /// トランザクションのバイト列が不正
+    InvalidTransaction(&'static str),
+    /// スクリプトのバイト列が不正
+    InvalidScript(&'static str),
+    /// スクリプトの実行が失敗した
+    ScriptFailed(&'static str),
+    /// 楕円曲線の演算で発生したエラー
+    EllipticCurve(elliptic_curve::Error),
+    /// 有限体の演算で発生したエラー
+    FieldElement(field_element::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingPrivateKey => write!(f, "Private key is not set"),
+            Error::InvalidPrivateKey => write!(f, "Private key is not in range 1 to n - 1"),
+            Error::InvalidPublicKey => write!(f, "Public key is infinity or not on the curve"),
+            Error::InvalidTweak => write!(f, "Tweak is out of range or produces an invalid key"),
+            Error::InvalidSecLength { expected, actual } => write!(
+                f,
+                "Invalid SEC length: expected {} bytes, got {}",
+                expected, actual
+            ),
+            Error::InvalidSecPrefix(prefix) => write!(f, "Invalid SEC prefix: {:#04x}", prefix),
+            Error::InvalidRecoveryId(id) => write!(f, "Invalid recovery id: {}", id),
+            Error::InvalidSignature => write!(f, "Invalid signature"),
+            Error::InvalidNonce => write!(f, "Nonce is out of range or produces r or s of zero"),
+            Error::BatchVerification { index } => {
+                write!(f, "Invalid signature at index {} in batch", index)
+            }
+            Error::InvalidBase58Character(c) => write!(f, "Invalid Base58 character: {:?}", c),
+            Error::InvalidBase58Checksum => write!(f, "Invalid Base58Check checksum"),
+            Error::InvalidAddressVersion(version) => {
+                write!(f, "Invalid address version: {:#04x}", version)
+            }
+            Error::InvalidAddressLength(length) => {
+                write!(f, "Invalid address payload length: {}", length)
+            }
+            Error::InvalidWifPrefix(prefix) => write!(f, "Invalid WIF prefix: {:#04x}", prefix),
+            Error::InvalidWifLength(length) => write!(f, "Invalid WIF payload length: {}", length),
+            Error::InvalidWifCompressionFlag(flag) => {
+                write!(f, "Invalid WIF compression flag: {:#04x}", flag)
+            }
+            Error::InvalidBech32(reason) => write!(f, "Invalid bech32: {}", reason),
+            Error::InvalidSegwitHrp(hrp) => {
+                write!(f, "Unknown human-readable part for segwit address: {}", hrp)
+            }
+            Error::InvalidWitnessVersion(version) => {
+                write!(f, "Invalid witness version: {}", version)
+            }
+            Error::InvalidWitnessProgramLength(length) => {
+                write!(f, "Invalid witness program length: {}", length)
+            }
+            Error::UnexpectedEndOfData => write!(f, "Unexpected end of data"),
+            Error::InvalidTransaction(reason) => write!(f, "Invalid transaction: {}", reason),
+            Error::InvalidScript(reason) => write!(f, "Invalid script: {}", reason),
+            Error::ScriptFailed(reason) => write!(f, "Script failed: {}", reason),
+            Error::EllipticCurve(e) => write!(f, "{}", e),
+            Error::FieldElement(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::EllipticCurve(e) => Some(e),
+            Error::FieldElement(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<elliptic_curve::Error> for Error {
+    fn from(e: elliptic_curve::Error) -> Self {
+        Error::EllipticCurve(e)
+    }
+}
+
+impl From<field_element::Error> for Error {
+    fn from(e: field_element::Error) -> Self {
+        Error::FieldElement(e)
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::OnceLock;
+
+use elliptic_curve::{Ecdsa, Point};
+use field_element::{Fe256, FieldElement};
+use rug::{ops::RemRounding, Integer};
+
+use crate::secp256k1::Secp256k1;
+
+/// secp256k1 の自己準同型 φ(x, y) = (βx, y) = λ(x, y) の定数と、スカラー分解用の格子の基底
+struct Glv {
+    /// λ^3 ≡ 1 (mod n)
+    lambda: Integer,
+    /// β^3 ≡ 1 (mod p)
+    beta: Fe256,
+    /// a_i + b_i λ ≡ 0 (mod n) を満たす短いベクトル (a1, b1), (a2, b2)
+    a1: Integer,
+    b1: Integer,
+    a2: Integer,
+    b2: Integer,
+}
+
+fn glv() -> &'static Glv {
+    static GLV: OnceLock<Glv> = OnceLock::new();
+    GLV.get_or_init(|| {
+        let hex = |s: &str| Integer::from_str_radix(s, 16).unwrap();
+        let a1 = hex("3086d221a7d46bcde86c90e49284eb15");
+        Glv {
+            lambda: hex("5363ad4cc05c30e0a5261c028812645a122e22ea20816678df02967c1b23bd72"),
+            beta: Fe256::try_from_integer(&hex(
+                "7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee",
+            ))
+            .unwrap(),
+            b1: -hex("e4437ed6010e88286f547fa90abfe4c3"),
+            a2: hex("114ca50f7a8e2f3f657c1108d9d44cfd8"),
+            b2: a1.clone(),
+            a1,
+        }
+    })
+}
+
+/// k ≡ k1 + k2 λ (mod n) となる約128bitの k1, k2 に分解する
+pub(crate) fn decompose(k: &Integer) -> (Integer, Integer) {
+    let glv = glv();
+    let n = Secp256k1::get_n();
+    let k = k.clone().rem_euc(&n);
+
+    // c1 = round(b2 k / n), c2 = round(-b1 k / n)
+    let half = n.clone() / 2;
+    let c1: Integer = (glv.b2.clone() * &k + &half) / &n;
+    let c2: Integer = (-glv.b1.clone() * &k + &half) / &n;
+
+    let k1 = k - c1.clone() * &glv.a1 - c2.clone() * &glv.a2;
+    let k2 = -c1 * &glv.b1 - c2 * &glv.b2;
+    (k1, k2)
+}
+
+pub(crate) fn lambda() -> &'static Integer {
+    &glv().lambda
+}
+
+/// φ(P) = (βx, y) = λP
+pub(crate) fn endomorphism(point: &Point<Fe256, Integer>) -> Point<Fe256, Integer> {
+    Point::new(point.x.map(|x| x * glv().beta), point.y, point.a, point.b)
+}
+
+impl Secp256k1 {
+    /// Σ k_i P_i をGLV法で計算する
+    ///
+    /// 各スカラーを約128bitの2つに分解して点の数を倍にし、マルチスカラー倍算でまとめて計算する。
+    /// 実行時間がスカラーに依存するので、署名検証など公開データにのみ使う。
+    /// 点が1つもなければ無限遠点を返す
+    pub(crate) fn linear_combination(
+        terms: &[(Point<FieldElement<Integer>, Integer>, Integer)],
+    ) -> Point<FieldElement<Integer>, Integer> {
+        if terms.is_empty() {
+            return Secp256k1::create_point(None, None);
+        }
+        let mut split_terms = vec![];
+        for (point, k) in terms {
+            let point = Secp256k1::to_fe256_point(point.clone());
+            let (k1, k2) = decompose(k);
+            split_terms.push((endomorphism(&point), k2));
+            split_terms.push((point, k1));
+        }
+        Secp256k1::from_fe256_point(
+            Point::multi_scalar_mul(&split_terms).expect("split_terms is not empty"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::rand::RandState;
+
+    use super::*;
+
+    #[test]
+    fn test_glv_constants() {
+        let glv = glv();
+        let lambda = lambda().clone();
+        assert_eq!(
+            lambda
+                .clone()
+                .pow_mod(&Integer::from(3), &Secp256k1::get_n())
+                .unwrap(),
+            1
+        );
+        let n = Secp256k1::get_n();
+        let g = Secp256k1::to_fe256_point(Secp256k1::get_g());
+
+        assert_eq!(endomorphism(&g), g.clone() * lambda.clone());
+        assert_eq!((glv.a1.clone() + glv.b1.clone() * &lambda).rem_euc(&n), 0);
+        assert_eq!((glv.a2.clone() + glv.b2.clone() * &lambda).rem_euc(&n), 0);
+    }
+
+    #[test]
+    fn test_decompose() {
+        let n = Secp256k1::get_n();
+        let lambda = lambda().clone();
+        let mut rand = RandState::new();
+
+        let mut scalars = vec![Integer::from(0), Integer::from(1), n.clone() - 1];
+        for _ in 0..64 {
+            scalars.push(Integer::from(n.random_below_ref(&mut rand)));
+        }
+        for k in scalars {
+            let (k1, k2) = decompose(&k);
+            assert_eq!((k1.clone() + k2.clone() * &lambda).rem_euc(&n), k);
+            assert!(k1.significant_bits() <= 129);
+            assert!(k2.significant_bits() <= 129);
+        }
+    }
+
+    #[test]
+    fn test_linear_combination() {
+        let n = Secp256k1::get_n();
+        let g = Secp256k1::get_g();
+        let p = g.clone() * Integer::from(0xdeadbeefu32);
+        let mut rand = RandState::new();
+
+        for _ in 0..4 {
+            let u = Integer::from(n.random_below_ref(&mut rand));
+            let v = Integer::from(n.random_below_ref(&mut rand));
+
+            assert_eq!(
+                Secp256k1::linear_combination(&[(g.clone(), u.clone())]),
+                g.clone() * u.clone()
+            );
+            assert_eq!(
+                Secp256k1::linear_combination(&[(g.clone(), u.clone()), (p.clone(), v.clone())]),
+                g.clone() * u + p.clone() * v
+            );
+        }
+
+        let infinity = Secp256k1::create_point(None, None);
+        assert_eq!(
+            Secp256k1::linear_combination(&[(g.clone(), n.clone())]),
+            infinity
+        );
+        assert_eq!(
+            Secp256k1::linear_combination(&[(g.clone(), Integer::from(1)), (g, n - 1)]),
+            infinity
+        );
+        assert_eq!(Secp256k1::linear_combination(&[]), infinity);
+    }
+}
@@ -1,4 +1,5 @@
 use hmac::{Hmac, Mac};
+use ripemd::Ripemd160;
 use sha2::{Digest, Sha256};
 
 type HmacSha256 = Hmac<Sha256>;
@@ -9,6 +10,34 @@ pub fn create_sha256_from_string(s: &str) -> Vec<u8> {
     hasher.finalize().as_slice().to_vec()
 }
 
+pub fn create_sha256(bytes: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finalize().as_slice().to_vec()
+}
+
+/// SHA256を2回適用したハッシュ (チェックサムやtxidに使う)
+pub fn create_hash256(bytes: &[u8]) -> Vec<u8> {
+    create_sha256(&create_sha256(bytes))
+}
+
+/// SHA256の後にRIPEMD160を適用したハッシュ (アドレスに使う)
+pub fn create_hash160(bytes: &[u8]) -> Vec<u8> {
+    let mut hasher = Ripemd160::new();
+    hasher.update(create_sha256(bytes));
+    hasher.finalize().as_slice().to_vec()
+}
+
+/// BIP340 のタグ付きハッシュ SHA256(SHA256(tag) || SHA256(tag) || msg)
+pub fn create_tagged_hash(tag: &str, msg: &[u8]) -> Vec<u8> {
+    let tag_hash = create_sha256_from_string(tag);
+    let mut hasher = Sha256::new();
+    hasher.update(&tag_hash);
+    hasher.update(&tag_hash);
+    hasher.update(msg);
+    hasher.finalize().as_slice().to_vec()
+}
+
 pub fn create_hmac256(secret: &[u8], message: &[u8]) -> Vec<u8> {
     let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
     mac.update(message);
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use elliptic_curve::{Ecdsa, Point};
+use field_element::{FieldElement, Scalar};
+use rug::{integer::Order, Integer};
+use zeroize::Zeroizing;
+
+use crate::{
+    secp256k1::{scalar_to_integer, to_32_bytes, to_scalar, Secp256k1},
+    secret::{zeroize_integer, Redacted},
+    Error,
+};
+
+/// 1からn-1の範囲にあることが保証された秘密鍵
+///
+/// drop 時にメモリ上の値を消去し、`Debug` では値を表示しない。
+/// 消去されない複製が残らないよう `Clone` は実装しない
+#[derive(PartialEq, Eq)]
+pub struct SecretKey(Integer);
+
+/// 無限遠点でなく曲線上にあることが保証された公開鍵
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey(Point<FieldElement<Integer>, Integer>);
+
+impl SecretKey {
+    pub fn new(mut secret: Integer) -> Result<Self, Error> {
+        if secret < 1 || secret >= Secp256k1::get_n() {
+            // 範囲外でも秘密の値として渡されたものなので消去してから捨てる
+            zeroize_integer(&mut secret);
+            return Err(Error::InvalidPrivateKey);
+        }
+        Ok(SecretKey(secret))
+    }
+
+    /// 32バイトのビッグエンディアンから変換する
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, Error> {
+        SecretKey::new(Integer::from_digits(bytes, Order::MsfBe))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        to_32_bytes(&self.0)
+    }
+
+    pub fn secret(&self) -> &Integer {
+        &self.0
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(Secp256k1::mul_generator(&self.0))
+    }
+
+    /// (self + tweak) mod n
+    pub fn tweak_add(&self, tweak: &Integer) -> Result<Self, Error> {
+        tweak_add_scalar(&Zeroizing::new(to_scalar(&self.0)), tweak)
+    }
+
+    /// (self * tweak) mod n
+    pub fn tweak_mul(&self, tweak: &Integer) -> Result<Self, Error> {
+        if *tweak < 1 || *tweak >= Secp256k1::get_n() {
+            return Err(Error::InvalidTweak);
+        }
+        let product = Zeroizing::new(to_scalar(&self.0) * to_scalar(tweak));
+        SecretKey::from_scalar(&product).map_err(|_| Error::InvalidTweak)
+    }
+
+    /// n - self
+    pub fn negate(&self) -> Self {
+        SecretKey(scalar_to_integer(&Zeroizing::new(-to_scalar(&self.0))))
+    }
+
+    fn from_scalar(scalar: &Scalar) -> Result<Self, Error> {
+        if bool::from(scalar.is_zero()) {
+            return Err(Error::InvalidPrivateKey);
+        }
+        Ok(SecretKey(scalar_to_integer(scalar)))
+    }
+}
+
+/// (secret + tweak) mod n を秘密鍵にする
+///
+/// `SecretKey` を作らずに借りた秘密鍵を調整できるよう、スカラーのまま受け取る
+pub(crate) fn tweak_add_scalar(secret: &Scalar, tweak: &Integer) -> Result<SecretKey, Error> {
+    if *tweak < 0 || *tweak >= Secp256k1::get_n() {
+        return Err(Error::InvalidTweak);
+    }
+    let sum = Zeroizing::new(secret + &to_scalar(tweak));
+    SecretKey::from_scalar(&sum).map_err(|_| Error::InvalidTweak)
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SecretKey").field(&Redacted).finish()
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        zeroize_integer(&mut self.0);
+    }
+}
+
+impl PublicKey {
+    pub fn new(point: Point<FieldElement<Integer>, Integer>) -> Result<Self, Error> {
+        if point.x.is_none() {
+            return Err(Error::InvalidPublicKey);
+        }
+        // フィールドは公開されているので、曲線上の点かどうかを改めて確認する
+        let g = Secp256k1::get_g();
+        if point.a != g.a || point.b != g.b {
+            return Err(Error::InvalidPublicKey);
+        }
+        Point::try_new(point.x, point.y, point.a, point.b)
+            .map(PublicKey)
+            .map_err(|_| Error::InvalidPublicKey)
+    }
+
+    pub fn from_sec(sec: &[u8]) -> Result<Self, Error> {
+        PublicKey::new(Secp256k1::parse_sec(sec)?.public_key.clone())
+    }
+
+    pub fn sec(&self, compress: bool) -> Vec<u8> {
+        Secp256k1::new(None, self.0.clone()).sec(compress)
+    }
+
+    pub fn point(&self) -> &Point<FieldElement<Integer>, Integer> {
+        &self.0
+    }
+
+    /// self + tweak * G
+    pub fn tweak_add(&self, tweak: &Integer) -> Result<Self, Error> {
+        if *tweak < 0 || *tweak >= Secp256k1::get_n() {
+            return Err(Error::InvalidTweak);
+        }
+        PublicKey::new(self.0.clone() + Secp256k1::mul_generator(tweak))
+            .map_err(|_| Error::InvalidTweak)
+    }
+
+    /// tweak * self
+    pub fn tweak_mul(&self, tweak: &Integer) -> Result<Self, Error> {
+        if *tweak < 1 || *tweak >= Secp256k1::get_n() {
+            return Err(Error::InvalidTweak);
+        }
+        PublicKey::new(Secp256k1::scalar_multiplication(
+            self.0.clone(),
+            tweak.clone(),
+        ))
+    }
+
+    /// y座標を反転した点
+    pub fn negate(&self) -> Self {
+        let y = self.0.y.clone().unwrap();
+        let neg_y = Secp256k1::create_field_element((y.prime.clone() - y.num) % &y.prime);
+        PublicKey(Secp256k1::create_point(self.0.x.clone(), Some(neg_y)))
+    }
+}
+
+impl Secp256k1 {
+    /// 秘密鍵を範囲を確認した `SecretKey` として取り出す
+    ///
+    /// 複製を作らないよう鍵ペアを消費し、値は移すだけにする
+    pub fn into_secret_key(mut self) -> Result<SecretKey, Error> {
+        self.checked_private_key()?;
+        Ok(SecretKey(self.private_key.take().unwrap()))
+    }
+
+    /// 秘密鍵の範囲を確認し、複製せずに参照を返す
+    pub(crate) fn checked_private_key(&self) -> Result<&Integer, Error> {
+        let private_key = self.private_key.as_ref().ok_or(Error::MissingPrivateKey)?;
+        if *private_key < 1 || *private_key >= Secp256k1::get_n() {
+            return Err(Error::InvalidPrivateKey);
+        }
+        Ok(private_key)
+    }
+}
+
+impl From<SecretKey> for Secp256k1 {
+    fn from(mut secret_key: SecretKey) -> Self {
+        let public_key = secret_key.public_key();
+        // drop 時に消去されるので、値は複製せずに移す
+        Secp256k1::new(Some(std::mem::take(&mut secret_key.0)), public_key.0)
+    }
+}
+
+impl From<PublicKey> for Secp256k1 {
+    fn from(public_key: PublicKey) -> Self {
+        Secp256k1::new(None, public_key.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_key() {
+        let n = Secp256k1::get_n();
+        for secret in [
+            Integer::from(0),
+            Integer::from(-1),
+            n.clone(),
+            n.clone() + 1,
+        ] {
+            assert_eq!(SecretKey::new(secret), Err(Error::InvalidPrivateKey));
+        }
+        assert_eq!(
+            SecretKey::from_bytes(&[0xff; 32]),
+            Err(Error::InvalidPrivateKey)
+        );
+
+        let secret_key = SecretKey::new(Integer::from(12345)).unwrap();
+        assert_eq!(
+            SecretKey::from_bytes(&secret_key.to_bytes()),
+            SecretKey::new(Integer::from(12345))
+        );
+        assert_eq!(
+            secret_key.public_key().point(),
+            &(Secp256k1::get_g() * Integer::from(12345))
+        );
+        assert_eq!(secret_key.negate().negate(), secret_key);
+        assert_eq!(
+            secret_key.negate().public_key(),
+            secret_key.public_key().negate()
+        );
+
+        let sec256 = Secp256k1::from(SecretKey::new(Integer::from(12345)).unwrap());
+        assert_eq!(sec256.into_secret_key(), Ok(secret_key));
+        assert_eq!(
+            Secp256k1::new(None, Secp256k1::get_g()).into_secret_key(),
+            Err(Error::MissingPrivateKey)
+        );
+        assert_eq!(
+            Secp256k1::new(Some(n), Secp256k1::get_g()).into_secret_key(),
+            Err(Error::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_secret_debug_redacted() {
+        let secret_key = SecretKey::new(Integer::from(0xdeadbeefu32)).unwrap();
+        assert_eq!(format!("{:?}", secret_key), "SecretKey(<redacted>)");
+
+        let sec256 = Secp256k1::from(secret_key);
+        let debug = format!("{:?}", sec256);
+        assert!(debug.starts_with("Secp256k1 { private_key: Some(<redacted>), public_key: "));
+        assert!(!debug.contains("3735928559"));
+        assert!(!debug.to_lowercase().contains("deadbeef"));
+    }
+
+    #[test]
+    fn test_tweak() {
+        let n = Secp256k1::get_n();
+        let secret_key = SecretKey::new(Integer::from(12345)).unwrap();
+        let public_key = secret_key.public_key();
+        let tweak = n.clone() - 100;
+
+        // 秘密鍵と公開鍵のどちらに tweak を適用しても対応が保たれる
+        let added = secret_key.tweak_add(&tweak).unwrap();
+        assert_eq!(added.secret(), &Integer::from(12245));
+        assert_eq!(added.public_key(), public_key.tweak_add(&tweak).unwrap());
+
+        let multiplied = secret_key.tweak_mul(&tweak).unwrap();
+        assert_eq!(
+            multiplied.public_key(),
+            public_key.tweak_mul(&tweak).unwrap()
+        );
+
+        // 結果が0 (無限遠点) になる tweak や範囲外の tweak は不正
+        let cancel = n.clone() - 12345;
+        assert_eq!(secret_key.tweak_add(&cancel), Err(Error::InvalidTweak));
+        assert_eq!(public_key.tweak_add(&cancel), Err(Error::InvalidTweak));
+        assert_eq!(secret_key.tweak_add(&n), Err(Error::InvalidTweak));
+        assert_eq!(
+            secret_key.tweak_mul(&Integer::from(0)),
+            Err(Error::InvalidTweak)
+        );
+        assert_eq!(public_key.tweak_mul(&n), Err(Error::InvalidTweak));
+    }
+
+    #[test]
+    fn test_public_key() {
+        let g = Secp256k1::get_g();
+        let public_key = PublicKey::new(g.clone()).unwrap();
+        assert_eq!(
+            PublicKey::from_sec(&public_key.sec(true)),
+            Ok(public_key.clone())
+        );
+        assert_eq!(PublicKey::from_sec(&public_key.sec(false)), Ok(public_key));
+
+        assert_eq!(
+            PublicKey::new(Secp256k1::create_point(None, None)),
+            Err(Error::InvalidPublicKey)
+        );
+
+        // 公開フィールドを書き換えた曲線外の点
+        let mut off_curve = g;
+        off_curve.y = off_curve.x.clone();
+        assert_eq!(PublicKey::new(off_curve), Err(Error::InvalidPublicKey));
+    }
+}
@@ -0,0 +1,21 @@
+pub mod address;
+pub mod base58;
+pub mod batch;
+pub mod bech32;
+pub mod error;
+mod glv;
+pub mod hash;
+pub mod keys;
+pub mod network;
+pub mod random;
+pub mod rfc6979;
+pub mod schnorr;
+pub mod script;
+pub mod secp256k1;
+mod secret;
+pub mod sighash;
+pub mod taproot;
+pub mod tx;
+pub mod wif;
+pub use error::Error;
+pub use network::Network;
@@ -3,10 +3,7 @@ use std::{io, process::exit, str::FromStr};
 use elliptic_curve::{Ecdsa, Signature};
 use rug::{integer::Order, Integer};
 
-use crate::{hash::create_sha256_from_string, secp256k1::Secp256k1};
-
-mod hash;
-mod secp256k1;
+use secp256k1_rust::{hash::create_sha256_from_string, secp256k1::Secp256k1};
 
 fn main() {
     println!("Hello, Secp256k1!\n");
@@ -41,10 +38,16 @@ fn verify() {
     let mut y = String::new();
     let y = read_line(&mut y);
 
-    let public_key = Secp256k1::generate_public_key_from_coord(
+    let public_key = match Secp256k1::generate_public_key_from_coord(
         Integer::from_str(x).unwrap(),
         Integer::from_str(y).unwrap(),
-    );
+    ) {
+        Ok(public_key) => public_key,
+        Err(e) => {
+            println!("\nInvalid public key: {}", e);
+            exit(1);
+        }
+    };
     let sec256 = Secp256k1::new(None, public_key);
 
     println!("\n4. Please input the message: ");
@@ -91,8 +94,8 @@ fn sign() {
         Order::MsfBe,
     );
 
-    let k = sec256.deterministic_k(message.clone());
-    let signature = sec256.sign(message, k);
+    let k = sec256.deterministic_k(message.clone()).unwrap();
+    let signature = sec256.sign(message, k).unwrap();
 
     println!("\n{:?}", signature);
 }
@@ -0,0 +1,50 @@
+/// アドレスや鍵の形式を決めるビットコインのネットワーク
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    /// P2PKHアドレスのバージョンバイト
+    pub fn p2pkh_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet => 0x6f,
+        }
+    }
+
+    pub fn from_p2pkh_prefix(prefix: u8) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.p2pkh_prefix() == prefix)
+    }
+
+    /// WIF形式の秘密鍵のプレフィックス
+    pub fn wif_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet => 0xef,
+        }
+    }
+
+    pub fn from_wif_prefix(prefix: u8) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.wif_prefix() == prefix)
+    }
+
+    /// SegWitアドレスの人間が読める部分
+    pub fn bech32_hrp(self) -> &'static str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+        }
+    }
+
+    pub fn from_bech32_hrp(hrp: &str) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.bech32_hrp() == hrp)
+    }
+}
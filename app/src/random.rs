@@ -0,0 +1,99 @@
+use field_element::Scalar;
+use rand_core::{CryptoRng, OsRng, RngCore};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::secp256k1::{scalar_to_integer, Secp256k1};
+
+impl Secp256k1 {
+    /// OSの乱数生成器から秘密鍵を選んで鍵ペアを生成する
+    pub fn generate_random() -> Self {
+        Secp256k1::generate_random_with(&mut OsRng)
+    }
+
+    /// 与えられた乱数生成器から秘密鍵を選んで鍵ペアを生成する
+    ///
+    /// 256bitの乱数が 1..n に入るまで引き直すので、秘密鍵は一様に分布する。
+    /// 捨てた候補も含め、乱数のバッファとスカラーは drop 時に消去する
+    pub fn generate_random_with<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        loop {
+            rng.fill_bytes(&mut *bytes);
+            // n 以上の値は還元すると元のバイト列と一致しなくなるので、0と一緒に捨てる
+            let private_key = Zeroizing::new(Scalar::from_bytes_reduced(&bytes));
+            let in_range = Zeroizing::new(private_key.to_bytes())[..].ct_eq(&bytes[..]);
+            if bool::from(in_range & !private_key.is_zero()) {
+                return Secp256k1::from_private_key(scalar_to_integer(&private_key)).unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Ecdsa;
+    use rand_core::impls;
+    use rug::{integer::Order, Integer};
+
+    use super::*;
+    use crate::{secp256k1::to_32_bytes, Error};
+
+    /// 決められた32バイトの値を順に返す乱数生成器
+    struct FixedRng(Vec<[u8; 32]>);
+
+    impl RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.copy_from_slice(&self.0.remove(0));
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for FixedRng {}
+
+    #[test]
+    fn test_generate_random() {
+        let n = Secp256k1::get_n();
+
+        // 0 と n 以上の値は捨てられる
+        let mut rng = FixedRng(vec![[0; 32], to_32_bytes(&n), [0xff; 32], [0x11; 32]]);
+        let sec256 = Secp256k1::generate_random_with(&mut rng);
+        let expected = Integer::from_digits(&[0x11u8; 32], Order::MsfBe);
+        assert_eq!(sec256.private_key, Some(expected.clone()));
+        assert_eq!(sec256.public_key, Secp256k1::get_g() * expected);
+        assert!(rng.0.is_empty());
+
+        let mut rng = FixedRng(vec![to_32_bytes(&(n.clone() - 1))]);
+        let sec256 = Secp256k1::generate_random_with(&mut rng);
+        assert_eq!(sec256.private_key, Some(n.clone() - 1));
+
+        let sec1 = Secp256k1::generate_random();
+        let sec2 = Secp256k1::generate_random();
+        assert_ne!(sec1.private_key, sec2.private_key);
+        let private_key = sec1.private_key.clone().unwrap();
+        assert!(private_key >= 1 && private_key < n);
+        assert_eq!(Secp256k1::from_private_key(private_key), Ok(sec1));
+    }
+
+    #[test]
+    fn test_from_private_key_invalid() {
+        let n = Secp256k1::get_n();
+        for private_key in [Integer::from(0), Integer::from(-1), n.clone(), n + 1] {
+            assert_eq!(
+                Secp256k1::from_private_key(private_key),
+                Err(Error::InvalidPrivateKey)
+            );
+        }
+    }
+}
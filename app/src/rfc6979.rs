@@ -0,0 +1,138 @@
+use hmac::{
+    digest::{core_api::BlockSizeUser, Digest},
+    Mac, SimpleHmac,
+};
+use rug::{integer::Order, Integer};
+use zeroize::Zeroizing;
+
+/// RFC 6979 に従って、秘密鍵 `x` とメッセージハッシュ `h1` から決定的にnonceを生成する
+///
+/// `q` は群の位数、`D` はHMACに使うハッシュ関数。
+/// `extra_data` が空でなければ 3.6 節の追加データとして K の計算に含める
+/// (Bitcoin Core は low-R の探索で32バイトのカウンタを渡す)
+pub fn generate_k<D>(q: &Integer, x: &Integer, h1: &[u8], extra_data: &[u8]) -> Integer
+where
+    D: Digest + BlockSizeUser,
+{
+    let qlen = q.significant_bits();
+    let hlen = <D as Digest>::output_size();
+
+    // K, V と秘密鍵のバイト列は drop 時に消去する
+    let x_octets = Zeroizing::new(int2octets(x, qlen));
+    let h1_octets = bits2octets(h1, q);
+    let mut v = Zeroizing::new(vec![0x01; hlen]);
+    let mut k = Zeroizing::new(vec![0x00; hlen]);
+
+    k = hmac::<D>(&k, &[&v, &[0x00], &x_octets, &h1_octets, extra_data]);
+    v = hmac::<D>(&k, &[&v]);
+    k = hmac::<D>(&k, &[&v, &[0x01], &x_octets, &h1_octets, extra_data]);
+    v = hmac::<D>(&k, &[&v]);
+
+    loop {
+        let mut t = Zeroizing::new(Vec::with_capacity(qlen as usize / 8 + hlen));
+        while (t.len() as u32) * 8 < qlen {
+            v = hmac::<D>(&k, &[&v]);
+            t.extend_from_slice(&v);
+        }
+
+        let candidate = bits2int(&t, qlen);
+        if candidate >= 1 && candidate < *q {
+            return candidate;
+        }
+
+        k = hmac::<D>(&k, &[&v, &[0x00]]);
+        v = hmac::<D>(&k, &[&v]);
+    }
+}
+
+fn hmac<D>(key: &[u8], message: &[&[u8]]) -> Zeroizing<Vec<u8>>
+where
+    D: Digest + BlockSizeUser,
+{
+    let mut mac = SimpleHmac::<D>::new_from_slice(key).expect("HMAC can take key of any size");
+    for part in message {
+        mac.update(part);
+    }
+    Zeroizing::new(mac.finalize().into_bytes().to_vec())
+}
+
+/// バイト列の先頭 qlen bit を整数として取り出す (2.3.2)
+fn bits2int(bytes: &[u8], qlen: u32) -> Integer {
+    let num = Integer::from_digits(bytes, Order::MsfBe);
+    let blen = bytes.len() as u32 * 8;
+    if blen > qlen {
+        num >> (blen - qlen)
+    } else {
+        num
+    }
+}
+
+/// 整数を rlen = 8 * ceil(qlen / 8) bit の固定長のバイト列にする (2.3.3)
+fn int2octets(num: &Integer, qlen: u32) -> Vec<u8> {
+    let rlen = qlen.div_ceil(8) as usize;
+    // 秘密鍵も渡されるので、長さを求めずに固定長のバッファへ直接書き込む (上位は0で埋まる)
+    let mut bytes = vec![0u8; rlen];
+    num.write_digits(&mut bytes, Order::MsfBe);
+    bytes
+}
+
+/// bits2int の結果を q で還元してから固定長のバイト列にする (2.3.4)
+fn bits2octets(bytes: &[u8], q: &Integer) -> Vec<u8> {
+    let qlen = q.significant_bits();
+    let z1 = bits2int(bytes, qlen);
+    // z1 < 2^qlen < 2q なので1回引けば十分
+    let z2 = if z1 >= *q { z1 - q } else { z1 };
+    int2octets(&z2, qlen)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Sha256, Sha512};
+
+    use super::*;
+
+    fn hex(s: &str) -> Integer {
+        Integer::from_str_radix(s, 16).unwrap()
+    }
+
+    #[test]
+    fn test_rfc6979_appendix_a1() {
+        // A.1.3: qlen = 163 で、ハッシュの方が長い場合の切り詰めと還元
+        let q = hex("4000000000000000000020108A2E0CC0D99F8A5EF");
+        let x = hex("09A4D6792295A7F730FC3F2B49CBC0F62E862272F");
+        let h1 = Sha256::digest(b"sample");
+
+        assert_eq!(
+            int2octets(&x, 163),
+            hex::decode("009A4D6792295A7F730FC3F2B49CBC0F62E862272F").unwrap()
+        );
+        assert_eq!(
+            bits2octets(&h1, &q),
+            hex::decode("01795EDF0D54DB760F156D0DAC04C0322B3A204224").unwrap()
+        );
+        assert_eq!(
+            generate_k::<Sha256>(&q, &x, &h1, &[]),
+            hex("23AF4074C90A02B3FE61D286D5C87F425E6BDD81B")
+        );
+    }
+
+    #[test]
+    fn test_rfc6979_p256() {
+        // A.2.5: ECDSA, 256 Bits (Prime Field)
+        let q = hex("FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551");
+        let x = hex("C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721");
+
+        assert_eq!(
+            generate_k::<Sha256>(&q, &x, &Sha256::digest(b"sample"), &[]),
+            hex("A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60")
+        );
+        assert_eq!(
+            generate_k::<Sha512>(&q, &x, &Sha512::digest(b"sample"), &[]),
+            hex("5FA81C63109BADB88C1F367B47DA606DA28CAD69AA22C4FE6AD7DF73A7173AA5")
+        );
+        assert_eq!(
+            generate_k::<Sha256>(&q, &x, &Sha256::digest(b"test"), &[]),
+            hex("D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0")
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use elliptic_curve::{Ecdsa, Point};
+use field_element::{FieldElement, Scalar};
+use rug::{integer::Order, Integer};
+use subtle::Choice;
+use zeroize::Zeroizing;
+
+use crate::{
+    hash::create_tagged_hash,
+    secp256k1::{to_32_bytes, to_scalar, Secp256k1},
+    Error,
+};
+
+/// BIP340 Schnorr署名
+impl Secp256k1 {
+    /// 公開鍵のx座標のみを32バイトで返す
+    pub fn xonly_public_key(&self) -> [u8; 32] {
+        to_32_bytes(&self.public_key.x.clone().unwrap().num)
+    }
+
+    /// BIP340 に従ってメッセージに署名する
+    pub fn schnorr_sign(&self, msg: &[u8], aux_rand: &[u8; 32]) -> Result<[u8; 64], Error> {
+        let d0 = Zeroizing::new(to_scalar(self.checked_private_key()?));
+
+        // 公開鍵のyが偶数になるよう秘密鍵を反転する。秘密の値は分岐せずに選択する
+        let public_key = Secp256k1::mul_generator_scalar(&d0);
+        let d = Zeroizing::new(Scalar::conditional_select(
+            &-&*d0,
+            &d0,
+            Choice::from(u8::from(has_even_y(&public_key))),
+        ));
+        let p_bytes = to_32_bytes(&public_key.x.unwrap().num);
+
+        // nonce の導出途中の値も秘密鍵と同じく drop 時に消去する
+        let aux_hash = create_tagged_hash("BIP0340/aux", aux_rand);
+        let d_bytes = Zeroizing::new(d.to_bytes());
+        let t: Zeroizing<Vec<u8>> = Zeroizing::new(
+            d_bytes
+                .iter()
+                .zip(aux_hash.iter())
+                .map(|(a, b)| a ^ b)
+                .collect(),
+        );
+        let rand: Zeroizing<[u8; 32]> = Zeroizing::new(
+            create_tagged_hash(
+                "BIP0340/nonce",
+                &Zeroizing::new([&t, &p_bytes[..], msg].concat()),
+            )
+            .try_into()
+            .unwrap(),
+        );
+        let k0 = Zeroizing::new(Scalar::from_bytes_reduced(&rand));
+        if bool::from(k0.is_zero()) {
+            return Err(Error::InvalidSignature);
+        }
+
+        let big_r = Secp256k1::mul_generator_scalar(&k0);
+        let k = Zeroizing::new(Scalar::conditional_select(
+            &-&*k0,
+            &k0,
+            Choice::from(u8::from(has_even_y(&big_r))),
+        ));
+        let r_bytes = to_32_bytes(&big_r.x.unwrap().num);
+
+        let e = challenge(&r_bytes, &p_bytes, msg);
+        let s = to_scalar(&e) * &*d + &*k;
+
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&r_bytes);
+        sig[32..].copy_from_slice(&s.to_bytes());
+
+        if !Secp256k1::schnorr_verify(msg, &p_bytes, &sig) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(sig)
+    }
+
+    /// BIP340 に従って署名を検証する
+    pub fn schnorr_verify(msg: &[u8], xonly_public_key: &[u8; 32], sig: &[u8; 64]) -> bool {
+        let n = Secp256k1::get_n();
+
+        let public_key = match Secp256k1::try_create_field_element(Integer::from_digits(
+            xonly_public_key,
+            Order::MsfBe,
+        ))
+        .and_then(|x| Secp256k1::lift_x(x, false))
+        {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        };
+
+        let r = Integer::from_digits(&sig[..32], Order::MsfBe);
+        let s = Integer::from_digits(&sig[32..], Order::MsfBe);
+        if Secp256k1::try_create_field_element(r.clone()).is_err() || s >= n {
+            return false;
+        }
+
+        // R = sG - eP
+        let e = challenge(&sig[..32], xonly_public_key, msg);
+        let big_r =
+            Secp256k1::linear_combination(&[(Secp256k1::get_g(), s), (public_key, n.clone() - e)]);
+        if big_r.x.is_none() || !has_even_y(&big_r) {
+            return false;
+        }
+        big_r.x.unwrap().num == r
+    }
+}
+
+pub(crate) fn has_even_y(point: &Point<FieldElement<Integer>, Integer>) -> bool {
+    point.y.clone().unwrap().num.is_even()
+}
+
+fn challenge(r_bytes: &[u8], p_bytes: &[u8], msg: &[u8]) -> Integer {
+    let e = create_tagged_hash("BIP0340/challenge", &[r_bytes, p_bytes, msg].concat());
+    Integer::from_digits(e.as_slice(), Order::MsfBe) % Secp256k1::get_n()
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Ecdsa;
+    use hex::ToHex;
+    use rug::Integer;
+
+    use super::*;
+
+    fn decode<const N: usize>(s: &str) -> [u8; N] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    fn key_pair(secret_key: &str) -> Secp256k1 {
+        let private_key = Integer::from_str_radix(secret_key, 16).unwrap();
+        let public_key = Secp256k1::get_g() * private_key.clone();
+        Secp256k1::new(Some(private_key), public_key)
+    }
+
+    #[test]
+    fn test_schnorr_sign_vectors() {
+        // BIP340 test vectors 0-3, 15-18 (15 以降は長さが32バイトでないメッセージ)
+        let vectors = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+            ),
+            (
+                "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+            ),
+            (
+                "C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9",
+                "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+                "C87AA53824B4D7AE2EB035A2B5BBBCCC080E76CDC6D1692C4B0B62D798E6D906",
+                "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+                "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+            ),
+            (
+                "0B432B2677937381AEF05BB02A66ECD012773062CF3FA2549E44F58ED2401710",
+                "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+                "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+            ),
+            (
+                "0340034003400340034003400340034003400340034003400340034003400340",
+                "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "",
+                "71535DB165ECD9FBBC046E5FFAEA61186BB6AD436732FCCC25291A55895464CF6069CE26BF03466228F19A3A62DB8A649F2D560FAC652827D1AF0574E427AB63",
+            ),
+            (
+                "0340034003400340034003400340034003400340034003400340034003400340",
+                "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "11",
+                "08A20A0AFEF64124649232E0693C583AB1B9934AE63B4C3511F3AE1134C6A303EA3173BFEA6683BD101FA5AA5DBC1996FE7CACFC5A577D33EC14564CEC2BACBF",
+            ),
+            (
+                "0340034003400340034003400340034003400340034003400340034003400340",
+                "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0102030405060708090A0B0C0D0E0F1011",
+                "5130F39A4059B43BC7CAC09A19ECE52B5D8699D1A71E3C52DA9AFDB6B50AC370C4A482B77BF960F8681540E25B6771ECE1E5A37FD80E5A51897C5566A97EA5A5",
+            ),
+            (
+                "0340034003400340034003400340034003400340034003400340034003400340",
+                "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                &"99".repeat(100),
+                "403B12B0D8555A344175EA7EC746566303321E5DBFA8BE6F091635163ECA79A8585ED3E3170807E7C03B720FC54C7B23897FCBA0E9D0B4A06894CFD249F22367",
+            ),
+        ];
+
+        for (secret_key, public_key, aux_rand, msg, sig) in vectors {
+            let sec256 = key_pair(secret_key);
+            let msg = hex::decode(msg).unwrap();
+
+            assert_eq!(
+                sec256.xonly_public_key().encode_hex_upper::<String>(),
+                public_key
+            );
+            let signature = sec256.schnorr_sign(&msg, &decode(aux_rand)).unwrap();
+            assert_eq!(signature.encode_hex_upper::<String>(), sig);
+            assert!(Secp256k1::schnorr_verify(
+                &msg,
+                &decode(public_key),
+                &signature
+            ));
+        }
+    }
+
+    #[test]
+    fn test_schnorr_verify_vectors() {
+        // BIP340 test vectors 4-14
+        let vectors = [
+            (
+                "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+                "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+                "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+                true,
+            ),
+            // 公開鍵が曲線上にない
+            (
+                "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+                false,
+            ),
+            // Rのyが奇数
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+                false,
+            ),
+            // メッセージを反転している
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+                false,
+            ),
+            // sを反転している
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+                false,
+            ),
+            // sG - eP が無限遠点 (x(inf) を0とみなすと通ってしまう)
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+                false,
+            ),
+            // sG - eP が無限遠点 (x(inf) を1とみなすと通ってしまう)
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+                false,
+            ),
+            // rが曲線上の点のx座標でない
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+                false,
+            ),
+            // rがp以上
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+                false,
+            ),
+            // sがn以上
+            (
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+                false,
+            ),
+            // 公開鍵がp以上
+            (
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+                false,
+            ),
+        ];
+
+        for (public_key, msg, sig, expected) in vectors {
+            assert_eq!(
+                Secp256k1::schnorr_verify(
+                    &hex::decode(msg).unwrap(),
+                    &decode(public_key),
+                    &decode(sig)
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_schnorr_sign_tampered() {
+        let sec256 = Secp256k1::generate_key_pair_from_secret("my secret");
+        let msg = b"my message";
+        let mut sig = sec256.schnorr_sign(msg, &[0u8; 32]).unwrap();
+
+        assert!(Secp256k1::schnorr_verify(
+            msg,
+            &sec256.xonly_public_key(),
+            &sig
+        ));
+        assert!(!Secp256k1::schnorr_verify(
+            b"my message2",
+            &sec256.xonly_public_key(),
+            &sig
+        ));
+        sig[63] ^= 0x01;
+        assert!(!Secp256k1::schnorr_verify(
+            msg,
+            &sec256.xonly_public_key(),
+            &sig
+        ));
+    }
+
+    #[test]
+    fn test_schnorr_sign_invalid_key() {
+        let public_key = Secp256k1::get_g();
+        assert_eq!(
+            Secp256k1::new(None, public_key.clone()).schnorr_sign(b"", &[0u8; 32]),
+            Err(Error::MissingPrivateKey)
+        );
+        assert_eq!(
+            Secp256k1::new(Some(Secp256k1::get_n()), public_key).schnorr_sign(b"", &[0u8; 32]),
+            Err(Error::InvalidPrivateKey)
+        );
+    }
+}
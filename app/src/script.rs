@@ -0,0 +1,707 @@
+use elliptic_curve::{Ecdsa, Signature};
+use rug::{integer::Order, Integer};
+
+use crate::{
+    hash::{create_hash160, create_hash256, create_sha256},
+    secp256k1::Secp256k1,
+    tx::Tx,
+    Error,
+};
+
+pub const OP_0: u8 = 0x00;
+pub const OP_PUSHDATA1: u8 = 0x4c;
+pub const OP_PUSHDATA2: u8 = 0x4d;
+pub const OP_PUSHDATA4: u8 = 0x4e;
+pub const OP_1NEGATE: u8 = 0x4f;
+pub const OP_1: u8 = 0x51;
+pub const OP_16: u8 = 0x60;
+pub const OP_NOP: u8 = 0x61;
+pub const OP_VERIFY: u8 = 0x69;
+pub const OP_RETURN: u8 = 0x6a;
+pub const OP_DROP: u8 = 0x75;
+pub const OP_DUP: u8 = 0x76;
+pub const OP_SWAP: u8 = 0x7c;
+pub const OP_EQUAL: u8 = 0x87;
+pub const OP_EQUALVERIFY: u8 = 0x88;
+pub const OP_SHA256: u8 = 0xa8;
+pub const OP_HASH160: u8 = 0xa9;
+pub const OP_HASH256: u8 = 0xaa;
+pub const OP_CHECKSIG: u8 = 0xac;
+pub const OP_CHECKSIGVERIFY: u8 = 0xad;
+pub const OP_CHECKMULTISIG: u8 = 0xae;
+pub const OP_CHECKMULTISIGVERIFY: u8 = 0xaf;
+
+/// OP_CHECKMULTISIG で使える公開鍵の最大数
+const MAX_PUBKEYS_PER_MULTISIG: i64 = 20;
+/// 数値として読み取るスタックの要素の最大バイト数
+const MAX_NUM_SIZE: usize = 4;
+/// スタックに積めるデータの最大バイト数
+const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+/// 実行できるスクリプトの最大バイト数
+const MAX_SCRIPT_SIZE: usize = 10_000;
+
+/// スクリプトを構成するオペコード、またはスタックに積むデータ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Op(u8),
+    Push(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Script {
+    pub commands: Vec<Command>,
+}
+
+impl Script {
+    pub fn new(commands: Vec<Command>) -> Self {
+        Script { commands }
+    }
+
+    /// スクリプトのバイト列 (先頭の長さを除く) を読み込む
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut commands = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            let op = bytes[i];
+            i += 1;
+
+            let len_size = match op {
+                OP_PUSHDATA1 => 1,
+                OP_PUSHDATA2 => 2,
+                OP_PUSHDATA4 => 4,
+                0x01..=0x4b => 0,
+                OP_0 => {
+                    commands.push(Command::Push(vec![]));
+                    continue;
+                }
+                _ => {
+                    commands.push(Command::Op(op));
+                    continue;
+                }
+            };
+            let len = if len_size == 0 {
+                op as usize
+            } else {
+                let len_bytes = bytes
+                    .get(i..i + len_size)
+                    .ok_or(Error::InvalidScript("push length past end of script"))?;
+                i += len_size;
+                len_bytes
+                    .iter()
+                    .rev()
+                    .fold(0usize, |acc, &b| (acc << 8) | b as usize)
+            };
+            let data = i
+                .checked_add(len)
+                .and_then(|end| bytes.get(i..end))
+                .ok_or(Error::InvalidScript("push past end of script"))?;
+            i += len;
+            commands.push(Command::Push(data.to_vec()));
+        }
+        Ok(Script { commands })
+    }
+
+    /// データは長さに応じた最短のプレフィックスで出力する
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut result = vec![];
+        for command in &self.commands {
+            match command {
+                Command::Op(op) => result.push(*op),
+                Command::Push(data) => push_data(&mut result, data),
+            }
+        }
+        result
+    }
+
+    /// OP_DUP OP_HASH160 <hash160> OP_EQUALVERIFY OP_CHECKSIG
+    pub fn p2pkh(hash160: &[u8; 20]) -> Self {
+        Script::new(vec![
+            Command::Op(OP_DUP),
+            Command::Op(OP_HASH160),
+            Command::Push(hash160.to_vec()),
+            Command::Op(OP_EQUALVERIFY),
+            Command::Op(OP_CHECKSIG),
+        ])
+    }
+
+    /// OP_HASH160 <redeemScript の hash160> OP_EQUAL
+    pub fn p2sh(hash160: &[u8; 20]) -> Self {
+        Script::new(vec![
+            Command::Op(OP_HASH160),
+            Command::Push(hash160.to_vec()),
+            Command::Op(OP_EQUAL),
+        ])
+    }
+
+    /// OP_m <公開鍵>... OP_n OP_CHECKMULTISIG
+    pub fn multisig(m: u8, public_keys: &[Vec<u8>]) -> Result<Self, Error> {
+        if !(1..=16).contains(&m) || (m as usize) > public_keys.len() || public_keys.len() > 16 {
+            return Err(Error::InvalidScript("multisig requires 1 <= m <= n <= 16"));
+        }
+        let mut commands = vec![Command::Op(OP_1 + m - 1)];
+        commands.extend(public_keys.iter().map(|key| Command::Push(key.clone())));
+        commands.push(Command::Op(OP_1 + public_keys.len() as u8 - 1));
+        commands.push(Command::Op(OP_CHECKMULTISIG));
+        Ok(Script::new(commands))
+    }
+
+    pub fn is_p2sh(&self) -> bool {
+        matches!(
+            self.commands.as_slice(),
+            [Command::Op(OP_HASH160), Command::Push(hash), Command::Op(OP_EQUAL)] if hash.len() == 20
+        )
+    }
+
+    /// データを積むだけのスクリプトか (P2SH の scriptSig に求められる)
+    pub fn is_push_only(&self) -> bool {
+        self.commands.iter().all(|command| match command {
+            Command::Push(_) => true,
+            Command::Op(op) => *op == OP_1NEGATE || (OP_1..=OP_16).contains(op),
+        })
+    }
+
+    /// スタックマシンでスクリプトを実行する
+    fn execute(&self, stack: &mut Vec<Vec<u8>>, checker: &Checker) -> Result<(), Error> {
+        for command in &self.commands {
+            let op = match command {
+                Command::Push(data) => {
+                    if data.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                        return Err(Error::ScriptFailed("push exceeds 520 bytes"));
+                    }
+                    stack.push(data.clone());
+                    continue;
+                }
+                Command::Op(op) => *op,
+            };
+
+            match op {
+                OP_1NEGATE => stack.push(encode_num(-1)),
+                OP_1..=OP_16 => stack.push(encode_num(i64::from(op - OP_1 + 1))),
+                OP_NOP => {}
+                OP_VERIFY => verify(stack)?,
+                OP_RETURN => return Err(Error::ScriptFailed("OP_RETURN")),
+                OP_DROP => {
+                    pop(stack)?;
+                }
+                OP_DUP => {
+                    let top = stack.last().ok_or(Error::ScriptFailed("stack underflow"))?;
+                    stack.push(top.clone());
+                }
+                OP_SWAP => {
+                    let len = stack.len();
+                    if len < 2 {
+                        return Err(Error::ScriptFailed("stack underflow"));
+                    }
+                    stack.swap(len - 1, len - 2);
+                }
+                OP_EQUAL | OP_EQUALVERIFY => {
+                    let a = pop(stack)?;
+                    let b = pop(stack)?;
+                    stack.push(encode_bool(a == b));
+                    if op == OP_EQUALVERIFY {
+                        verify(stack)?;
+                    }
+                }
+                OP_SHA256 => {
+                    let top = pop(stack)?;
+                    stack.push(create_sha256(&top));
+                }
+                OP_HASH160 => {
+                    let top = pop(stack)?;
+                    stack.push(create_hash160(&top));
+                }
+                OP_HASH256 => {
+                    let top = pop(stack)?;
+                    stack.push(create_hash256(&top));
+                }
+                OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                    let public_key = pop(stack)?;
+                    let sig = pop(stack)?;
+                    stack.push(encode_bool(checker.check_sig(&sig, &public_key)));
+                    if op == OP_CHECKSIGVERIFY {
+                        verify(stack)?;
+                    }
+                }
+                OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                    let ok = check_multisig(stack, checker)?;
+                    stack.push(encode_bool(ok));
+                    if op == OP_CHECKMULTISIGVERIFY {
+                        verify(stack)?;
+                    }
+                }
+                _ => return Err(Error::ScriptFailed("unsupported opcode")),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tx {
+    /// 入力の scriptSig と、使う出力の scriptPubKey を続けて実行して検証する
+    ///
+    /// P2PKH、ベアマルチシグ、P2SH (BIP16) などのレガシーな入力に対応する。
+    /// 520バイトを超えるデータのプッシュと10000バイトを超えるスクリプトは拒否するが、
+    /// OP_CODESEPARATOR、scriptCode からの署名の削除 (FindAndDelete)、
+    /// 命令数 (201) とスタックの要素数 (1000) の上限は扱わない
+    pub fn verify_input(&self, input_index: usize, script_pubkey: &[u8]) -> Result<(), Error> {
+        let tx_in = self
+            .tx_ins
+            .get(input_index)
+            .ok_or(Error::InvalidTransaction("input index out of range"))?;
+        let checker = |script_code| Checker {
+            tx: self,
+            input_index,
+            script_code,
+        };
+
+        let script_sig = parse_executable(&tx_in.script_sig)?;
+        let mut stack = vec![];
+        script_sig.execute(&mut stack, &checker(&tx_in.script_sig))?;
+        let p2sh_stack = stack.clone();
+
+        let pubkey_script = parse_executable(script_pubkey)?;
+        pubkey_script.execute(&mut stack, &checker(script_pubkey))?;
+        verify(&mut stack)?;
+
+        // P2SH では scriptSig が最後に積んだ redeemScript を残りのスタックで実行する
+        if pubkey_script.is_p2sh() {
+            if !script_sig.is_push_only() {
+                return Err(Error::ScriptFailed("P2SH scriptSig must be push only"));
+            }
+            let mut stack = p2sh_stack;
+            let redeem_script = pop(&mut stack)?;
+            parse_executable(&redeem_script)?.execute(&mut stack, &checker(&redeem_script))?;
+            verify(&mut stack)?;
+        }
+        Ok(())
+    }
+}
+
+/// 実行するスクリプトを読み込む。サイズの上限を超えるスクリプトは実行前に失敗させる
+fn parse_executable(bytes: &[u8]) -> Result<Script, Error> {
+    if bytes.len() > MAX_SCRIPT_SIZE {
+        return Err(Error::ScriptFailed("script exceeds 10000 bytes"));
+    }
+    Script::parse(bytes)
+}
+
+/// OP_CHECKSIG で署名ハッシュを求めるための、検証中の入力と scriptCode
+struct Checker<'a> {
+    tx: &'a Tx,
+    input_index: usize,
+    script_code: &'a [u8],
+}
+
+impl Checker<'_> {
+    /// 末尾に sighash type を付けたDER署名と、SEC形式の公開鍵で署名を検証する
+    fn check_sig(&self, sig: &[u8], public_key: &[u8]) -> bool {
+        let Some((&sighash_type, der)) = sig.split_last() else {
+            return false;
+        };
+        let (Ok(key), Ok(signature)) =
+            (Secp256k1::parse_sec(public_key), Signature::parse_der(der))
+        else {
+            return false;
+        };
+        let Ok(z) =
+            self.tx
+                .sig_hash_legacy(self.input_index, self.script_code, u32::from(sighash_type))
+        else {
+            return false;
+        };
+        key.verify(Integer::from_digits(&z, Order::MsfBe), signature)
+    }
+}
+
+/// スタックから <ダミー> <署名>... m <公開鍵>... n を取り出し、
+/// 署名がそれぞれ公開鍵と同じ順で対応しているかを確かめる
+fn check_multisig(stack: &mut Vec<Vec<u8>>, checker: &Checker) -> Result<bool, Error> {
+    let n = pop_num(stack)?;
+    if !(0..=MAX_PUBKEYS_PER_MULTISIG).contains(&n) {
+        return Err(Error::ScriptFailed("invalid public key count"));
+    }
+    let mut public_keys = (0..n).map(|_| pop(stack)).collect::<Result<Vec<_>, _>>()?;
+    public_keys.reverse();
+
+    let m = pop_num(stack)?;
+    if !(0..=n).contains(&m) {
+        return Err(Error::ScriptFailed("invalid signature count"));
+    }
+    let mut sigs = (0..m).map(|_| pop(stack)).collect::<Result<Vec<_>, _>>()?;
+    sigs.reverse();
+
+    // 実装の都合で1つ余分に取り出される要素
+    pop(stack)?;
+
+    let mut public_keys = public_keys.iter();
+    for sig in &sigs {
+        if !public_keys
+            .by_ref()
+            .any(|public_key| checker.check_sig(sig, public_key))
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn pop(stack: &mut Vec<Vec<u8>>) -> Result<Vec<u8>, Error> {
+    stack.pop().ok_or(Error::ScriptFailed("stack underflow"))
+}
+
+fn pop_num(stack: &mut Vec<Vec<u8>>) -> Result<i64, Error> {
+    let top = pop(stack)?;
+    if top.len() > MAX_NUM_SIZE {
+        return Err(Error::ScriptFailed("number overflow"));
+    }
+    Ok(decode_num(&top))
+}
+
+/// 先頭の要素を取り出し、偽なら失敗とする
+fn verify(stack: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+    if cast_to_bool(&pop(stack)?) {
+        Ok(())
+    } else {
+        Err(Error::ScriptFailed("verify failed"))
+    }
+}
+
+fn encode_bool(value: bool) -> Vec<u8> {
+    if value {
+        vec![0x01]
+    } else {
+        vec![]
+    }
+}
+
+/// 0 と負の0 (最後のバイトが 0x80 で他が 0) 以外は真
+fn cast_to_bool(bytes: &[u8]) -> bool {
+    match bytes.split_last() {
+        Some((&last, rest)) => rest.iter().any(|&b| b != 0) || (last & 0x7f) != 0,
+        None => false,
+    }
+}
+
+/// スクリプトの数値 (リトルエンディアンで最上位bitが符号) にエンコードする
+pub fn encode_num(num: i64) -> Vec<u8> {
+    let mut abs = num.unsigned_abs();
+    let mut result = vec![];
+    while abs > 0 {
+        result.push((abs & 0xff) as u8);
+        abs >>= 8;
+    }
+    match result.last_mut() {
+        Some(last) if *last & 0x80 != 0 => result.push(if num < 0 { 0x80 } else { 0x00 }),
+        Some(last) if num < 0 => *last |= 0x80,
+        _ => {}
+    }
+    result
+}
+
+pub fn decode_num(bytes: &[u8]) -> i64 {
+    let Some((&last, rest)) = bytes.split_last() else {
+        return 0;
+    };
+    let mut result = i64::from(last & 0x7f);
+    for &b in rest.iter().rev() {
+        result = (result << 8) | i64::from(b);
+    }
+    if last & 0x80 != 0 {
+        -result
+    } else {
+        result
+    }
+}
+
+/// データをスタックに積むスクリプトを追加する
+pub(crate) fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        len if len < OP_PUSHDATA1 as usize => script.push(len as u8),
+        len if len <= 0xff => script.extend([OP_PUSHDATA1, len as u8]),
+        len if len <= 0xffff => {
+            script.push(OP_PUSHDATA2);
+            script.extend((len as u16).to_le_bytes());
+        }
+        len => {
+            script.push(OP_PUSHDATA4);
+            script.extend((len as u32).to_le_bytes());
+        }
+    }
+    script.extend(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sighash::SIGHASH_ALL,
+        tx::{TxIn, TxOut},
+    };
+
+    // Programming Bitcoin 5章のメインネットのトランザクション (P2PKH) と、使用した出力の scriptPubKey
+    const P2PKH_TX: &str = "0100000001813f79011acb80925dfe69b3def355fe914bd1d96a3f5f71bf8303c6a989c7d1000000006b483045022100ed81ff192e75a3fd2304004dcadb746fa5e24c5031ccfcf21320b0277457c98f02207a986d955c6e0cb35d446a89d3f56100f4d7f67801c31967743a9c8e10615bed01210349fc4e631e3624a545de3f89f5d8684c7b8138bd94bdd531d2e213bf016b278afeffffff02a135ef01000000001976a914bc3b654dca7e56b04dca18f2566cdaf02e8d9ada88ac99c39800000000001976a9141c4bc762dd5423e332166702cb75f40df79fea1288ac19430600";
+    const P2PKH_PREV_SCRIPT_PUBKEY: &str = "76a914a802fc56c704ce87c42d7c92eb75e7896bdc41ae88ac";
+
+    // Programming Bitcoin 8章の 2-of-2 マルチシグの P2SH を使うメインネットのトランザクション
+    const P2SH_TX: &str = "0100000001868278ed6ddfb6c1ed3ad5f8181eb0c7a385aa0836f01d5e4789e6bd304d87221a000000db00483045022100dc92655fe37036f47756db8102e0d7d5e28b3beb83a8fef4f5dc0559bddfb94e02205a36d4e4e6c7fcd16658c50783e00c341609977aed3ad00937bf4ee942a8993701483045022100da6bee3c93766232079a01639d07fa869598749729ae323eab8eef53577d611b02207bef15429dcadce2121ea07f233115c6f09034c0be68db99980b9a6c5e75402201475221022626e955ea6ea6d98850c994f9107b036b1334f18ca8830bfff1295d21cfdb702103b287eaf122eea69030a0e9feed096bed8045c8b98bec453e1ffac7fbdbd4bb7152aeffffffff04d3b11400000000001976a914904a49878c0adfc3aa05de7afad2cc15f483a56a88ac7f400900000000001976a914418327e3f3dda4cf5b9089325a4b95abdfa0334088ac722c0c00000000001976a914ba35042cfe9fc66fd35ac2224eebdafd1028ad2788acdc4ace020000000017a91474d691da1574e6b3c192ecfb52cc8984ee7b6c568700000000";
+    const P2SH_PREV_SCRIPT_PUBKEY: &str = "a91474d691da1574e6b3c192ecfb52cc8984ee7b6c5687";
+
+    fn keys(secrets: &[u32]) -> Vec<Secp256k1> {
+        secrets
+            .iter()
+            .map(|&secret| Secp256k1::from_private_key(Integer::from(secret)).unwrap())
+            .collect()
+    }
+
+    fn unsigned_tx() -> Tx {
+        Tx {
+            version: 1,
+            tx_ins: (0..2).map(|i| TxIn::new([i as u8; 32], i)).collect(),
+            tx_outs: vec![TxOut {
+                amount: 10_000,
+                script_pubkey: Script::p2pkh(&[0x11; 20]).serialize(),
+            }],
+            locktime: 0,
+        }
+    }
+
+    /// scriptCode に対する SIGHASH_ALL のDER署名
+    fn sign(tx: &Tx, input_index: usize, script_code: &[u8], key: &Secp256k1) -> Vec<u8> {
+        let z = tx
+            .sig_hash_legacy(input_index, script_code, SIGHASH_ALL)
+            .unwrap();
+        let z = Integer::from_digits(&z, Order::MsfBe);
+        let k = key.deterministic_k(z.clone()).unwrap();
+        let mut sig = key.sign(z, k).unwrap().der();
+        sig.push(SIGHASH_ALL as u8);
+        sig
+    }
+
+    #[test]
+    fn test_parse_serialize() {
+        let bytes = hex::decode(P2PKH_PREV_SCRIPT_PUBKEY).unwrap();
+        let script = Script::parse(&bytes).unwrap();
+        assert_eq!(script, Script::p2pkh(&bytes[3..23].try_into().unwrap()));
+        assert_eq!(script.serialize(), bytes);
+
+        // OP_PUSHDATA を使うデータと OP_0
+        let script = Script::new(vec![
+            Command::Push(vec![]),
+            Command::Push(vec![0xab; 0x4c]),
+            Command::Push(vec![0xcd; 0x100]),
+            Command::Op(OP_CHECKSIG),
+        ]);
+        let bytes = script.serialize();
+        assert_eq!(bytes[..3], [OP_0, OP_PUSHDATA1, 0x4c]);
+        assert_eq!(Script::parse(&bytes).unwrap(), script);
+        assert!(Script::parse(&[OP_PUSHDATA4, 0x00, 0x00, 0x00, 0x00]).is_ok());
+
+        assert_eq!(
+            Script::parse(&[0x02, 0x00]),
+            Err(Error::InvalidScript("push past end of script"))
+        );
+        assert_eq!(
+            Script::parse(&[OP_PUSHDATA2, 0x01]),
+            Err(Error::InvalidScript("push length past end of script"))
+        );
+    }
+
+    #[test]
+    fn test_num() {
+        for (num, bytes) in [
+            (0, vec![]),
+            (1, vec![0x01]),
+            (-1, vec![0x81]),
+            (16, vec![0x10]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x00]),
+            (-128, vec![0x80, 0x80]),
+            (255, vec![0xff, 0x00]),
+            (256, vec![0x00, 0x01]),
+            (-256, vec![0x00, 0x81]),
+        ] {
+            assert_eq!(encode_num(num), bytes);
+            assert_eq!(decode_num(&bytes), num);
+        }
+
+        assert!(cast_to_bool(&[0x01]));
+        assert!(cast_to_bool(&[0x00, 0x80, 0x00]));
+        assert!(!cast_to_bool(&[]));
+        assert!(!cast_to_bool(&[0x00, 0x00]));
+        assert!(!cast_to_bool(&[0x00, 0x80]));
+    }
+
+    #[test]
+    fn test_verify_p2pkh() {
+        let tx = Tx::parse(&hex::decode(P2PKH_TX).unwrap()).unwrap();
+        let script_pubkey = hex::decode(P2PKH_PREV_SCRIPT_PUBKEY).unwrap();
+        assert_eq!(tx.verify_input(0, &script_pubkey), Ok(()));
+
+        // 別の公開鍵ハッシュの出力は使えない
+        let other = Script::p2pkh(&[0x11; 20]).serialize();
+        assert_eq!(
+            tx.verify_input(0, &other),
+            Err(Error::ScriptFailed("verify failed"))
+        );
+
+        // 出力を書き換えると署名が合わない
+        let mut tampered = tx.clone();
+        tampered.tx_outs[0].amount += 1;
+        assert_eq!(
+            tampered.verify_input(0, &script_pubkey),
+            Err(Error::ScriptFailed("verify failed"))
+        );
+        assert_eq!(
+            tx.verify_input(1, &script_pubkey),
+            Err(Error::InvalidTransaction("input index out of range"))
+        );
+    }
+
+    #[test]
+    fn test_verify_p2sh_mainnet() {
+        let tx = Tx::parse(&hex::decode(P2SH_TX).unwrap()).unwrap();
+        let script_pubkey = hex::decode(P2SH_PREV_SCRIPT_PUBKEY).unwrap();
+        assert!(Script::parse(&script_pubkey).unwrap().is_p2sh());
+        assert_eq!(tx.verify_input(0, &script_pubkey), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_multisig() {
+        let keys = keys(&[101, 202, 303]);
+        let public_keys: Vec<_> = keys.iter().map(|key| key.sec(true)).collect();
+        let redeem_script = Script::multisig(2, &public_keys).unwrap().serialize();
+        let p2sh = Script::p2sh(&create_hash160(&redeem_script).try_into().unwrap()).serialize();
+        let bare = Script::multisig(1, &public_keys[..2]).unwrap().serialize();
+
+        let mut tx = unsigned_tx();
+        // 入力0: P2SH の 2-of-3 (鍵0と鍵2で署名)
+        let sigs = [
+            sign(&tx, 0, &redeem_script, &keys[0]),
+            sign(&tx, 0, &redeem_script, &keys[2]),
+        ];
+        // 入力1: ベアマルチシグの 1-of-2 (鍵1で署名)
+        let sig = sign(&tx, 1, &bare, &keys[1]);
+
+        tx.tx_ins[0].script_sig = Script::new(vec![
+            Command::Push(vec![]),
+            Command::Push(sigs[0].clone()),
+            Command::Push(sigs[1].clone()),
+            Command::Push(redeem_script.clone()),
+        ])
+        .serialize();
+        tx.tx_ins[1].script_sig =
+            Script::new(vec![Command::Push(vec![]), Command::Push(sig)]).serialize();
+        assert_eq!(tx.verify_input(0, &p2sh), Ok(()));
+        assert_eq!(tx.verify_input(1, &bare), Ok(()));
+
+        // 署名の順序が公開鍵の順序と逆だと失敗する
+        let mut reordered = tx.clone();
+        reordered.tx_ins[0].script_sig = Script::new(vec![
+            Command::Push(vec![]),
+            Command::Push(sigs[1].clone()),
+            Command::Push(sigs[0].clone()),
+            Command::Push(redeem_script.clone()),
+        ])
+        .serialize();
+        assert_eq!(
+            reordered.verify_input(0, &p2sh),
+            Err(Error::ScriptFailed("verify failed"))
+        );
+
+        // scriptSig が redeemScript のハッシュに合わなければ失敗する
+        let other_redeem = Script::multisig(2, &public_keys[..2]).unwrap().serialize();
+        let mut wrong_redeem = tx.clone();
+        wrong_redeem.tx_ins[0].script_sig = Script::new(vec![
+            Command::Push(vec![]),
+            Command::Push(sigs[0].clone()),
+            Command::Push(sigs[1].clone()),
+            Command::Push(other_redeem),
+        ])
+        .serialize();
+        assert_eq!(
+            wrong_redeem.verify_input(0, &p2sh),
+            Err(Error::ScriptFailed("verify failed"))
+        );
+
+        // 出力を書き換えると署名が合わない
+        let mut tampered = tx.clone();
+        tampered.tx_outs[0].amount -= 1;
+        assert!(tampered.verify_input(0, &p2sh).is_err());
+        assert!(tampered.verify_input(1, &bare).is_err());
+    }
+
+    #[test]
+    fn test_multisig_invalid() {
+        let public_keys = vec![vec![0x02; 33]; 17];
+        for (m, n) in [(0, 1), (3, 2), (1, 17), (17, 17)] {
+            assert_eq!(
+                Script::multisig(m, &public_keys[..n]),
+                Err(Error::InvalidScript("multisig requires 1 <= m <= n <= 16"))
+            );
+        }
+        assert!(Script::multisig(16, &public_keys[..16]).is_ok());
+    }
+
+    #[test]
+    fn test_verify_size_limits() {
+        let mut tx = unsigned_tx();
+        let script_pubkey = vec![OP_DROP, OP_1];
+
+        tx.tx_ins[0].script_sig = Script::new(vec![Command::Push(vec![0xab; 520])]).serialize();
+        assert_eq!(tx.verify_input(0, &script_pubkey), Ok(()));
+
+        tx.tx_ins[0].script_sig = Script::new(vec![Command::Push(vec![0xab; 521])]).serialize();
+        assert_eq!(
+            tx.verify_input(0, &script_pubkey),
+            Err(Error::ScriptFailed("push exceeds 520 bytes"))
+        );
+
+        // 10000バイトちょうどのスクリプトまでは実行できる
+        tx.tx_ins[0].script_sig = vec![];
+        let mut script_pubkey = vec![OP_NOP; MAX_SCRIPT_SIZE - 1];
+        script_pubkey.push(OP_1);
+        assert_eq!(tx.verify_input(0, &script_pubkey), Ok(()));
+        script_pubkey.insert(0, OP_NOP);
+        assert_eq!(
+            tx.verify_input(0, &script_pubkey),
+            Err(Error::ScriptFailed("script exceeds 10000 bytes"))
+        );
+    }
+
+    #[test]
+    fn test_verify_p2sh_push_only() {
+        // redeemScript は OP_1 で、scriptSig に OP_DUP のような命令があれば P2SH では拒否する
+        let redeem_script = vec![OP_1];
+        let p2sh = Script::p2sh(&create_hash160(&redeem_script).try_into().unwrap()).serialize();
+
+        let mut tx = unsigned_tx();
+        tx.tx_ins[0].script_sig =
+            Script::new(vec![Command::Push(redeem_script.clone())]).serialize();
+        assert_eq!(tx.verify_input(0, &p2sh), Ok(()));
+
+        tx.tx_ins[0].script_sig = Script::new(vec![
+            Command::Push(redeem_script),
+            Command::Op(OP_DUP),
+            Command::Op(OP_DROP),
+        ])
+        .serialize();
+        assert_eq!(
+            tx.verify_input(0, &p2sh),
+            Err(Error::ScriptFailed("P2SH scriptSig must be push only"))
+        );
+    }
+
+    #[test]
+    fn test_push_data() {
+        for (len, prefix) in [
+            (0x4b, vec![0x4b]),
+            (0x4c, vec![0x4c, 0x4c]),
+            (0x100, vec![0x4d, 0x00, 0x01]),
+        ] {
+            let mut script = vec![];
+            push_data(&mut script, &vec![0xab; len]);
+            assert_eq!(script[..prefix.len()], prefix);
+            assert_eq!(script.len(), prefix.len() + len);
+        }
+    }
+}
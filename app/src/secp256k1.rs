@@ -1,18 +1,52 @@
-use elliptic_curve::{Ecdsa, Point, Signature};
-use num_traits::Pow;
-use rug::{integer::Order, ops::Pow as OtherPow, Integer};
+use std::{fmt, sync::OnceLock};
 
-use field_element::FieldElement;
+use elliptic_curve::{Ecdsa, FixedBaseTable, Point, Signature};
+use rug::{
+    integer::Order,
+    ops::{Pow as OtherPow, RemRounding},
+    Integer,
+};
 
-use crate::hash::{create_hmac256, create_sha256_from_string};
+use field_element::{Fe256, FieldElement, Scalar};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::{
+    hash::create_sha256_from_string,
+    keys::SecretKey,
+    rfc6979,
+    secret::{zeroize_integer, Redacted, SecretInteger},
+    Error,
+};
+
+#[derive(Clone, PartialEq, Eq)]
 pub struct Secp256k1 {
     pub private_key: Option<Integer>,
     pub public_key: Point<FieldElement<Integer>, Integer>,
 }
 
+// 秘密鍵はログなどに出力されないよう伏せる
+impl fmt::Debug for Secp256k1 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secp256k1")
+            .field("private_key", &self.private_key.as_ref().map(|_| Redacted))
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl Drop for Secp256k1 {
+    fn drop(&mut self) {
+        if let Some(private_key) = self.private_key.as_mut() {
+            zeroize_integer(private_key);
+        }
+    }
+}
+
 impl Ecdsa<FieldElement<Integer>, Integer> for Secp256k1 {
+    type Error = Error;
+
     fn new(
         private_key: Option<Integer>,
         public_key: Point<FieldElement<Integer>, Integer>,
@@ -23,231 +57,401 @@ impl Ecdsa<FieldElement<Integer>, Integer> for Secp256k1 {
         }
     }
 
+    // パスフレーズから作った鍵は推測されうるので、実際に使う鍵は generate_random で作る
     fn generate_key_pair_from_secret(secret: &str) -> Self {
-        let private_key =
-            Integer::from_digits(create_sha256_from_string(secret).as_slice(), Order::MsfBe);
-        let public_key = Secp256k1::get_g() * private_key.clone();
+        let hash: [u8; 32] = create_sha256_from_string(secret).try_into().unwrap();
+        let private_key = Zeroizing::new(Scalar::from_bytes_reduced(&hash));
         Self {
-            private_key: Some(private_key),
-            public_key,
+            private_key: Some(scalar_to_integer(&private_key)),
+            public_key: Secp256k1::mul_generator_scalar(&private_key),
         }
     }
 
     fn generate_public_key_from_coord(
         x: Integer,
         y: Integer,
-    ) -> Point<FieldElement<Integer>, Integer> {
-        let x = Secp256k1::create_field_element(x);
-        let y = Secp256k1::create_field_element(y);
-        Secp256k1::create_point(Some(x), Some(y))
+    ) -> Result<Point<FieldElement<Integer>, Integer>, Error> {
+        let x = Secp256k1::try_create_field_element(x)?;
+        let y = Secp256k1::try_create_field_element(y)?;
+        Secp256k1::try_create_point(Some(x), Some(y))
     }
 
     fn verify(&self, z: Integer, sig: Signature<Integer>) -> bool {
         let n = Secp256k1::get_n();
         let g = Secp256k1::get_g();
 
+        if sig.r < 1 || sig.r >= n || sig.s < 1 || sig.s >= n {
+            return false;
+        }
+
         let s_inv = sig.s.pow_mod(&(n.clone() - Integer::from(2)), &n).unwrap();
         let u = z * s_inv.clone() % &n;
         let v = sig.r.clone() * s_inv % &n;
-        let total = Secp256k1::scalar_multiplication(g, u)
-            + Secp256k1::scalar_multiplication(self.public_key.clone(), v);
-        if total.x.is_none() {
-            panic!("Total is at infinity");
+        let total = Secp256k1::linear_combination(&[(g, u), (self.public_key.clone(), v)]);
+        // r は x(R) を n で還元した値なので、x(R) >= n の場合も比較できるよう還元する
+        match total.x {
+            Some(x) => x.num % &n == sig.r,
+            None => false,
         }
-        total.x.unwrap() == Secp256k1::create_field_element(sig.r)
     }
 
-    fn sign(&self, z: Integer, k: Integer) -> Signature<Integer> {
-        if self.private_key.is_none() {
-            panic!("Private key is not set");
-        }
-
-        let n = Secp256k1::get_n();
-        let g = Secp256k1::get_g();
-
-        let r = (g * k.clone()).x.unwrap().num;
-        let k_inv = k.pow_mod(&(n.clone() - 2), &n).unwrap();
-        let mut s = (r.clone() * self.private_key.clone().unwrap() + z) * k_inv % n.clone();
-        if s > n.clone() / 2 {
-            s = n - s
-        }
-        Signature { r, s }
+    fn sign(&self, z: Integer, k: Integer) -> Result<Signature<Integer>, Error> {
+        let (signature, _) = self.sign_recoverable(z, k)?;
+        Ok(signature)
     }
 
     fn get_n() -> Integer {
-        Integer::from_str_radix(
-            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
-            16,
-        )
-        .unwrap()
+        static N: OnceLock<Integer> = OnceLock::new();
+        N.get_or_init(|| {
+            Integer::from_str_radix(
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+                16,
+            )
+            .unwrap()
+        })
+        .clone()
     }
 
     fn get_g() -> Point<FieldElement<Integer>, Integer> {
-        Secp256k1::create_point(
-            Some(Secp256k1::create_field_element(
-                Integer::from_str_radix(
-                    "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
-                    16,
-                )
-                .unwrap(),
-            )),
-            Some(Secp256k1::create_field_element(
-                Integer::from_str_radix(
-                    "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
-                    16,
-                )
-                .unwrap(),
-            )),
-        )
+        static G: OnceLock<Point<FieldElement<Integer>, Integer>> = OnceLock::new();
+        G.get_or_init(|| {
+            Secp256k1::create_point(
+                Some(Secp256k1::create_field_element(
+                    Integer::from_str_radix(
+                        "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                        16,
+                    )
+                    .unwrap(),
+                )),
+                Some(Secp256k1::create_field_element(
+                    Integer::from_str_radix(
+                        "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+                        16,
+                    )
+                    .unwrap(),
+                )),
+            )
+        })
+        .clone()
     }
 
-    fn deterministic_k(&self, mut z: Integer) -> Integer {
-        let n = Secp256k1::get_n();
-        if z > n {
-            z -= n.clone();
-        }
-        let mut k = [b'\x00'; 32].to_vec();
-        let mut v = [b'\x01'; 32].to_vec();
-        let z_bytes = z.to_digits::<u8>(Order::MsfBe);
-        let secret_bytes = self
-            .private_key
-            .clone()
-            .unwrap()
-            .to_digits::<u8>(Order::MsfBe);
-
-        v.push(b'\x00');
-        k = create_hmac256(
-            &k,
-            &[v.clone(), secret_bytes.clone(), z_bytes.clone()].concat(),
-        );
-
-        v = create_hmac256(&k, &v);
-
-        v.push(b'\x01');
-        k = create_hmac256(&k, ([v.clone(), secret_bytes, z_bytes].concat()).as_slice());
-
-        v = create_hmac256(&k, &v);
-
-        loop {
-            v = create_hmac256(&k, &v);
-
-            let candidate = Integer::from_digits(v.as_slice(), Order::MsfBe);
-            if candidate >= 1 && candidate < n {
-                return candidate;
-            }
-
-            v.push(b'\x00');
-            k = create_hmac256(&k, &v);
-
-            v = create_hmac256(&k, &v);
-        }
+    fn deterministic_k(&self, z: Integer) -> Result<Integer, Error> {
+        self.deterministic_k_with_data(&z, &[])
     }
 
     fn sec(&self, compress: bool) -> Vec<u8> {
+        let x = to_32_bytes(&self.public_key.x.clone().unwrap().num);
         if compress {
             let mut sec = vec![0x02];
             if self.public_key.y.clone().unwrap().num.is_odd() {
                 sec[0] = 0x03;
             }
-            sec.extend(
-                self.public_key
-                    .x
-                    .clone()
-                    .unwrap()
-                    .num
-                    .to_digits::<u8>(Order::MsfBe),
-            );
+            sec.extend(x);
             sec
         } else {
             let mut sec = vec![0x04];
-            sec.extend(
-                self.public_key
-                    .x
-                    .clone()
-                    .unwrap()
-                    .num
-                    .to_digits::<u8>(Order::MsfBe),
-            );
-            sec.extend(
-                self.public_key
-                    .y
-                    .clone()
-                    .unwrap()
-                    .num
-                    .to_digits::<u8>(Order::MsfBe),
-            );
+            sec.extend(x);
+            sec.extend(to_32_bytes(&self.public_key.y.clone().unwrap().num));
             sec
         }
     }
 
-    fn parse_sec(sec: &[u8]) -> Self {
-        if sec[0] == 0x04 {
+    fn parse_sec(sec: &[u8]) -> Result<Self, Error> {
+        let prefix = *sec.first().ok_or(Error::InvalidSecLength {
+            expected: 33,
+            actual: 0,
+        })?;
+
+        if prefix == 0x04 {
+            if sec.len() != 65 {
+                return Err(Error::InvalidSecLength {
+                    expected: 65,
+                    actual: sec.len(),
+                });
+            }
             let x = Integer::from_digits(&sec[1..33], Order::MsfBe);
             let y = Integer::from_digits(&sec[33..65], Order::MsfBe);
-            let public_key = Secp256k1::generate_public_key_from_coord(x, y);
-            return Self {
+            let public_key = Secp256k1::generate_public_key_from_coord(x, y)?;
+            return Ok(Self {
                 private_key: None,
                 public_key,
-            };
+            });
         }
 
-        let is_even = sec[0] == 0x02;
-        let x = Secp256k1::create_field_element(Integer::from_digits(&sec[1..], Order::MsfBe));
-        let alpha =
-            x.clone().pow(Integer::from(3)) + Secp256k1::create_field_element(Integer::from(7));
-        let beta = Secp256k1::sqrt(alpha);
-
-        let even_beta: FieldElement<Integer>;
-        let odd_beta: FieldElement<Integer>;
-        if beta.num.is_even() {
-            even_beta = beta.clone();
-            odd_beta = Secp256k1::create_field_element(x.prime.clone() - beta.num);
-        } else {
-            even_beta = Secp256k1::create_field_element(x.prime.clone() - beta.clone().num);
-            odd_beta = beta;
+        if prefix != 0x02 && prefix != 0x03 {
+            return Err(Error::InvalidSecPrefix(prefix));
         }
-        if is_even {
-            Self {
-                private_key: None,
-                public_key: Secp256k1::create_point(Some(x), Some(even_beta)),
-            }
-        } else {
-            Self {
-                private_key: None,
-                public_key: Secp256k1::create_point(Some(x), Some(odd_beta)),
-            }
+        if sec.len() != 33 {
+            return Err(Error::InvalidSecLength {
+                expected: 33,
+                actual: sec.len(),
+            });
         }
+
+        let x = Secp256k1::try_create_field_element(Integer::from_digits(&sec[1..], Order::MsfBe))?;
+        Ok(Self {
+            private_key: None,
+            public_key: Secp256k1::lift_x(x, prefix == 0x03)?,
+        })
     }
 }
 
 impl Secp256k1 {
-    fn create_field_element(num: Integer) -> FieldElement<Integer> {
-        let p = Integer::from(2).pow(256) - Integer::from(2).pow(32) - Integer::from(977);
-        FieldElement::new(num, p)
+    /// RFC 6979 (HMAC-SHA256) でnonceを生成する。`extra_data` は追加データとして混ぜる
+    pub fn deterministic_k_with_data(
+        &self,
+        z: &Integer,
+        extra_data: &[u8],
+    ) -> Result<Integer, Error> {
+        let private_key = self.checked_private_key()?;
+        // z はハッシュ値なので、32バイトに満たなければ先頭を0で埋める
+        let mut h1 = z.to_digits::<u8>(Order::MsfBe);
+        if h1.len() < 32 {
+            h1.splice(0..0, vec![0u8; 32 - h1.len()]);
+        }
+        Ok(rfc6979::generate_k::<Sha256>(
+            &Secp256k1::get_n(),
+            private_key,
+            &h1,
+            extra_data,
+        ))
+    }
+
+    /// RFC 6979 のnonceで署名する
+    ///
+    /// `low_r` が true なら Bitcoin Core と同じく、r < 2^255 になるまで追加データのカウンタを
+    /// 増やして署名し直す。DERエンコードが1バイト短く (70バイト以下に) なり、結果は決定的なまま
+    pub fn sign_deterministic(&self, z: Integer, low_r: bool) -> Result<Signature<Integer>, Error> {
+        let mut k = self.deterministic_k_with_data(&z, &[])?;
+        let mut counter: u32 = 0;
+        loop {
+            let signature = self.sign(z.clone(), k)?;
+            if !low_r || signature.r.significant_bits() < 256 {
+                return Ok(signature);
+            }
+            // カウンタは32バイトの先頭にリトルエンディアンで書く
+            counter += 1;
+            let mut extra_data = [0u8; 32];
+            extra_data[..4].copy_from_slice(&counter.to_le_bytes());
+            k = self.deterministic_k_with_data(&z, &extra_data)?;
+        }
+    }
+
+    /// 署名と合わせて公開鍵の復元に必要なリカバリーIDを返す
+    ///
+    /// リカバリーIDの下位1bitはRのy座標の偶奇、上位1bitはRのx座標がn以上かどうかを表す
+    pub fn sign_recoverable(
+        &self,
+        z: Integer,
+        k: Integer,
+    ) -> Result<(Signature<Integer>, u8), Error> {
+        let d = Zeroizing::new(to_scalar(self.checked_private_key()?));
+        let k = SecretInteger::new(k);
+        // k は n 未満の固定長のスカラーに変換し、以降の計算はすべて定数時間で行う
+        if *k < 0 || k.significant_bits() > 256 {
+            return Err(Error::InvalidNonce);
+        }
+        let k = Zeroizing::new(
+            Option::<Scalar>::from(Scalar::from_bytes(&Zeroizing::new(to_32_bytes(&k))))
+                .ok_or(Error::InvalidNonce)?,
+        );
+        if bool::from(k.is_zero()) {
+            return Err(Error::InvalidNonce);
+        }
+
+        let n = Secp256k1::get_n();
+
+        // k が範囲内なら R は無限遠点にならない
+        let big_r = Secp256k1::mul_generator_scalar(&k);
+        let r_x = big_r.x.unwrap().num;
+        let mut recovery_id = u8::from(big_r.y.unwrap().num.is_odd());
+        if r_x >= n {
+            recovery_id |= 2;
+        }
+
+        let r = r_x % &n;
+        if r == 0 {
+            return Err(Error::InvalidNonce);
+        }
+        let z = to_scalar(&z.rem_euc(&n));
+        let s = (to_scalar(&r) * &*d + z) * k.invert();
+        if bool::from(s.is_zero()) {
+            return Err(Error::InvalidNonce);
+        }
+        // s は公開される値なので分岐してよい。sを反転するとRも反転する
+        let s = if bool::from(s.is_high()) {
+            recovery_id ^= 1;
+            -s
+        } else {
+            s
+        };
+        Ok((
+            Signature {
+                r,
+                s: scalar_to_integer(&s),
+            },
+            recovery_id,
+        ))
     }
 
-    fn create_point(
+    /// 署名とリカバリーIDから公開鍵を復元する
+    pub fn recover(z: Integer, sig: Signature<Integer>, recovery_id: u8) -> Result<Self, Error> {
+        if recovery_id > 3 {
+            return Err(Error::InvalidRecoveryId(recovery_id));
+        }
+
+        let n = Secp256k1::get_n();
+        if sig.r < 1 || sig.r >= n || sig.s < 1 || sig.s >= n {
+            return Err(Error::InvalidSignature);
+        }
+
+        // Rのx座標を復元する
+        let mut r_x = sig.r.clone();
+        if recovery_id & 2 != 0 {
+            r_x += &n;
+        }
+        let r_x = Secp256k1::try_create_field_element(r_x)?;
+        let big_r = Secp256k1::lift_x(r_x, recovery_id & 1 == 1)?;
+
+        // Q = r^-1 (sR - zG)
+        let r_inv = sig.r.pow_mod(&(n.clone() - 2), &n).unwrap();
+        let u = (n.clone() - z % &n) * r_inv.clone() % &n;
+        let v = sig.s * r_inv % &n;
+        let public_key = Secp256k1::linear_combination(&[(Secp256k1::get_g(), u), (big_r, v)]);
+        if public_key.x.is_none() {
+            return Err(Error::InvalidSignature);
+        }
+
+        Ok(Self {
+            private_key: None,
+            public_key,
+        })
+    }
+
+    /// 秘密鍵が1からn-1の範囲にあることを確認し、公開鍵を求める
+    pub fn from_private_key(private_key: Integer) -> Result<Self, Error> {
+        Ok(Secp256k1::from(SecretKey::new(private_key)?))
+    }
+
+    /// 有限体の位数 p = 2^256 - 2^32 - 977
+    pub fn get_p() -> Integer {
+        static P: OnceLock<Integer> = OnceLock::new();
+        P.get_or_init(|| Integer::from(2).pow(256) - Integer::from(2).pow(32) - Integer::from(977))
+            .clone()
+    }
+
+    pub(crate) fn try_create_field_element(num: Integer) -> Result<FieldElement<Integer>, Error> {
+        Ok(FieldElement::try_new(num, Secp256k1::get_p())?)
+    }
+
+    pub(crate) fn create_field_element(num: Integer) -> FieldElement<Integer> {
+        Secp256k1::try_create_field_element(num).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_create_point(
         x: Option<FieldElement<Integer>>,
         y: Option<FieldElement<Integer>>,
-    ) -> Point<FieldElement<Integer>, Integer> {
+    ) -> Result<Point<FieldElement<Integer>, Integer>, Error> {
         let a = Secp256k1::create_field_element(Integer::from(0));
         let b = Secp256k1::create_field_element(Integer::from(7));
-        Point::new(x, y, a, b)
+        Ok(Point::try_new(x, y, a, b)?)
+    }
+
+    pub(crate) fn create_point(
+        x: Option<FieldElement<Integer>>,
+        y: Option<FieldElement<Integer>>,
+    ) -> Point<FieldElement<Integer>, Integer> {
+        Secp256k1::try_create_point(x, y).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    fn scalar_multiplication(
+    /// スカラー倍算は固定長の Fe256 上でGLV法を使って行う
+    ///
+    /// 実行時間がスカラーのbitに依存するので、署名検証など公開データにのみ使う
+    pub(crate) fn scalar_multiplication(
         point: Point<FieldElement<Integer>, Integer>,
-        mut coefficient: Integer,
+        coefficient: Integer,
+    ) -> Point<FieldElement<Integer>, Integer> {
+        Secp256k1::linear_combination(&[(point, coefficient)])
+    }
+
+    /// 生成元Gのスカラー倍
+    ///
+    /// 秘密のスカラーは固定長の `Scalar` に変換してから `mul_generator_scalar` で計算する。
+    /// 負の値や256bitを超える値は公開の値に限られるので、先に還元する
+    pub fn mul_generator(secret: &Integer) -> Point<FieldElement<Integer>, Integer> {
+        let scalar = if *secret < 0 || secret.significant_bits() > 256 {
+            to_scalar(&secret.clone().rem_euc(Secp256k1::get_n()))
+        } else {
+            to_scalar(secret)
+        };
+        Secp256k1::mul_generator_scalar(&Zeroizing::new(scalar))
+    }
+
+    /// 初回呼び出し時に作る事前計算テーブルを使い、秘密のスカラーでも定数時間で計算する
+    pub(crate) fn mul_generator_scalar(scalar: &Scalar) -> Point<FieldElement<Integer>, Integer> {
+        static TABLE: OnceLock<FixedBaseTable<Fe256, Integer>> = OnceLock::new();
+        let table = TABLE
+            .get_or_init(|| FixedBaseTable::new(Secp256k1::to_fe256_point(Secp256k1::get_g())));
+        let bytes = Zeroizing::new(scalar.to_bytes());
+        Secp256k1::from_fe256_point(table.mul_ct(&bytes).to_affine())
+    }
+
+    pub(crate) fn to_fe256_point(
+        point: Point<FieldElement<Integer>, Integer>,
+    ) -> Point<Fe256, Integer> {
+        let to_fe256 = |fe: FieldElement<Integer>| Fe256::try_from(fe).unwrap();
+        Point::new(
+            point.x.map(to_fe256),
+            point.y.map(to_fe256),
+            Fe256::ZERO,
+            Fe256::from_u64(7),
+        )
+    }
+
+    pub(crate) fn from_fe256_point(
+        point: Point<Fe256, Integer>,
     ) -> Point<FieldElement<Integer>, Integer> {
-        coefficient %= Secp256k1::get_n();
-        point * coefficient
+        Secp256k1::create_point(
+            point.x.map(FieldElement::from),
+            point.y.map(FieldElement::from),
+        )
     }
 
-    fn sqrt(num: FieldElement<Integer>) -> FieldElement<Integer> {
-        num.clone().pow((num.prime + 1) / 4)
+    /// x座標とyの偶奇から曲線上の点を求める
+    ///
+    /// 平方根は固定長の Fe256 で求める。平方根がなければyなしの点として曲線上のチェックで失敗させる
+    pub(crate) fn lift_x(
+        x: FieldElement<Integer>,
+        is_odd: bool,
+    ) -> Result<Point<FieldElement<Integer>, Integer>, Error> {
+        let x256 = Fe256::try_from(x.clone())?;
+        let alpha = x256.square() * x256 + Fe256::from_u64(7);
+
+        let y = alpha.sqrt().map(|beta| {
+            let y = if beta.is_odd() == is_odd { beta } else { -beta };
+            FieldElement::from(y)
+        });
+        Secp256k1::try_create_point(Some(x), y)
     }
 }
 
+/// 256bit以下の非負整数を32バイトのビッグエンディアンに変換する
+pub(crate) fn to_32_bytes(num: &Integer) -> [u8; 32] {
+    // 秘密の値も渡されるので、長さを求めずに固定長のバッファへ直接書き込む (上位は0で埋まる)
+    let mut bytes = [0u8; 32];
+    num.write_digits(&mut bytes, Order::MsfBe);
+    bytes
+}
+
+/// 256bit以下の非負整数を n で還元した `Scalar` に変換する
+pub(crate) fn to_scalar(num: &Integer) -> Scalar {
+    Scalar::from_bytes_reduced(&Zeroizing::new(to_32_bytes(num)))
+}
+
+pub(crate) fn scalar_to_integer(scalar: &Scalar) -> Integer {
+    Integer::from_digits(&Zeroizing::new(scalar.to_bytes())[..], Order::MsfBe)
+}
+
 #[cfg(test)]
 mod tests {
     use hex::ToHex;
@@ -279,7 +483,50 @@ mod tests {
         let point = Secp256k1::create_point(Some(x), Some(y));
         let point2 = Secp256k1::create_point(None, None);
 
-        assert_eq!(Secp256k1::scalar_multiplication(point, n), point2);
+        assert_eq!(
+            Secp256k1::scalar_multiplication(point.clone(), n.clone()),
+            point2
+        );
+        assert_eq!(Secp256k1::mul_generator(&n), point2);
+        assert_eq!(
+            Secp256k1::mul_generator(&Integer::from(-1)),
+            Secp256k1::mul_generator(&(n.clone() - 1))
+        );
+
+        // 定数時間版と可変時間版の結果は一致する
+        for k in [
+            Integer::from(1),
+            Integer::from(2),
+            Integer::from_str_radix("deadbeef12345", 16).unwrap(),
+            n.clone() - 1,
+            n.clone() + 5,
+        ] {
+            assert_eq!(
+                Secp256k1::mul_generator(&k),
+                Secp256k1::scalar_multiplication(point.clone(), k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fe256_point() {
+        let k = Integer::from_str_radix("deadbeef12345", 16).unwrap();
+        let g = Secp256k1::get_g();
+        let g256 = Secp256k1::to_fe256_point(g.clone());
+
+        assert_eq!(Secp256k1::from_fe256_point(g256.clone()), g);
+        assert_eq!(
+            Secp256k1::from_fe256_point(g256.clone() * k.clone()),
+            g.clone() * k
+        );
+        assert_eq!(
+            Secp256k1::from_fe256_point(g256.clone() + g256),
+            g.clone() + g
+        );
+        assert_eq!(
+            Secp256k1::to_fe256_point(Secp256k1::create_point(None, None)) * Integer::from(3),
+            Secp256k1::to_fe256_point(Secp256k1::create_point(None, None))
+        );
     }
 
     #[test]
@@ -340,6 +587,86 @@ mod tests {
         assert!(sec256.verify(z2, Signature { r: r2, s: s2 }));
     }
 
+    #[test]
+    fn test_deterministic_k() {
+        let n = Secp256k1::get_n();
+        let hex = |s: &str| Integer::from_str_radix(s, 16).unwrap();
+        let hash =
+            |s: &str| Integer::from_digits(create_sha256_from_string(s).as_slice(), Order::MsfBe);
+
+        for (secret, message, expected) in [
+            (
+                Integer::from(1),
+                "Satoshi Nakamoto",
+                "8F8A276C19F4149656B280621E358CCE24F5F52542772691EE69063B74F15D15",
+            ),
+            (
+                Integer::from(1),
+                "All those moments will be lost in time, like tears in rain. Time to die...",
+                "38AA22D72376B4DBC472E06C3BA403EE0A394DA63FC58D88686C611ABA98D6B3",
+            ),
+            (
+                n.clone() - 1,
+                "Satoshi Nakamoto",
+                "33A19B60E25FB6F4435AF53A3D42D493644827367E6453928554F43E49AA6F90",
+            ),
+            (
+                hex("f8b8af8ce3c7cca5e300d33939540c10d45ce001b8f252bfbc57ba0342904181"),
+                "Alan Turing",
+                "525A82B70E67874398067543FD84C83D30C175FDC45FDEEE082FE13B1D7CFDF1",
+            ),
+        ] {
+            let sec256 = Secp256k1::from_private_key(secret).unwrap();
+            assert_eq!(
+                sec256.deterministic_k(hash(message)).unwrap(),
+                hex(expected)
+            );
+        }
+
+        // 先頭が0のハッシュも32バイトとして扱い、追加データを渡すと別のnonceになる
+        let sec256 = Secp256k1::from_private_key(Integer::from(1)).unwrap();
+        let z = Integer::from(0x1234);
+        let k = sec256.deterministic_k(z.clone()).unwrap();
+        assert_eq!(
+            k,
+            rfc6979::generate_k::<Sha256>(&n, &Integer::from(1), &to_32_bytes(&z), &[])
+        );
+        assert_eq!(sec256.deterministic_k_with_data(&z, &[]).unwrap(), k);
+        assert_ne!(
+            sec256.deterministic_k_with_data(&z, &[0x01; 32]).unwrap(),
+            k
+        );
+    }
+
+    #[test]
+    fn test_sign_low_r() {
+        let sec256 = Secp256k1::generate_key_pair_from_secret("my secret");
+        let mut ground = 0;
+        for i in 0..16 {
+            let z = Integer::from_digits(
+                create_sha256_from_string(&format!("message {}", i)).as_slice(),
+                Order::MsfBe,
+            );
+            let plain = sec256.sign_deterministic(z.clone(), false).unwrap();
+            let k = sec256.deterministic_k(z.clone()).unwrap();
+            assert_eq!(plain, sec256.sign(z.clone(), k).unwrap());
+
+            let low_r = sec256.sign_deterministic(z.clone(), true).unwrap();
+            assert!(low_r.r.significant_bits() < 256);
+            assert!(low_r.der().len() <= 70);
+            assert!(sec256.verify(z.clone(), low_r.clone()));
+            assert_eq!(sec256.sign_deterministic(z, true).unwrap(), low_r);
+
+            if plain.r.significant_bits() < 256 {
+                assert_eq!(low_r, plain);
+            } else {
+                ground += 1;
+            }
+        }
+        // r の最上位bitが立つ確率は約1/2なので、いくつかは探索し直している
+        assert!(ground > 0);
+    }
+
     #[test]
     fn test_sign() {
         let message = Integer::from_digits(
@@ -352,13 +679,105 @@ mod tests {
         );
 
         let sec256 = Secp256k1::generate_key_pair_from_secret("my secret");
-        let k = sec256.deterministic_k(message.clone());
-        let signature = sec256.sign(message.clone(), k);
+        let k = sec256.deterministic_k(message.clone()).unwrap();
+        let signature = sec256.sign(message.clone(), k).unwrap();
 
         assert!(sec256.verify(message, signature.clone()));
         assert!(!sec256.verify(message2, signature));
     }
 
+    #[test]
+    fn test_recover() {
+        for (secret, message) in [
+            ("my secret", "my message"),
+            ("another secret", "another message"),
+            ("third secret", ""),
+        ] {
+            let z =
+                Integer::from_digits(create_sha256_from_string(message).as_slice(), Order::MsfBe);
+            let sec256 = Secp256k1::generate_key_pair_from_secret(secret);
+            let k = sec256.deterministic_k(z.clone()).unwrap();
+            let (signature, recovery_id) = sec256.sign_recoverable(z.clone(), k.clone()).unwrap();
+
+            assert_eq!(signature, sec256.sign(z.clone(), k).unwrap());
+            assert!(recovery_id < 2);
+
+            let recovered = Secp256k1::recover(z.clone(), signature.clone(), recovery_id).unwrap();
+            assert_eq!(recovered.public_key, sec256.public_key);
+            assert!(recovered.verify(z.clone(), signature.clone()));
+
+            // 偶奇が異なるRからは別の公開鍵が復元される
+            let other = Secp256k1::recover(z, signature, recovery_id ^ 1).unwrap();
+            assert_ne!(other.public_key, sec256.public_key);
+        }
+    }
+
+    #[test]
+    fn test_sign_invalid_nonce() {
+        let n = Secp256k1::get_n();
+        let sec256 = Secp256k1::from_private_key(Integer::from(12345)).unwrap();
+        let z = Integer::from(1);
+
+        for k in [
+            Integer::from(0),
+            Integer::from(-1),
+            n.clone(),
+            n.clone() + 1,
+        ] {
+            assert_eq!(sec256.sign(z.clone(), k), Err(Error::InvalidNonce));
+        }
+
+        // z = -r * d (mod n) だと s が0になる
+        let k = Integer::from(777);
+        let r = Secp256k1::mul_generator(&k).x.unwrap().num % &n;
+        let z = (n.clone() - r * Integer::from(12345) % &n) % &n;
+        assert_eq!(sec256.sign(z, k), Err(Error::InvalidNonce));
+    }
+
+    #[test]
+    fn test_verify_r_reduced() {
+        // x(R) >= n となるRに対して r = x(R) - n の署名を作り、その署名で検証できる公開鍵を復元する
+        let n = Secp256k1::get_n();
+        let z = Integer::from(1234);
+        let s = Integer::from(5678);
+        let (signature, public_key) = (0..)
+            .find_map(|i| {
+                let signature = Signature::new(Integer::from(i), s.clone());
+                Secp256k1::recover(z.clone(), signature.clone(), 2)
+                    .ok()
+                    .map(|key| (signature, key))
+            })
+            .unwrap();
+        assert!(signature.r.clone() + &n < Secp256k1::get_p());
+        assert!(public_key.verify(z.clone(), signature.clone()));
+        assert!(!public_key.verify(z + 1, signature));
+    }
+
+    #[test]
+    fn test_recover_invalid() {
+        let signature = Signature::new(Integer::from(1), Integer::from(1));
+        assert_eq!(
+            Secp256k1::recover(Integer::from(1), signature, 4),
+            Err(Error::InvalidRecoveryId(4))
+        );
+        assert_eq!(
+            Secp256k1::recover(
+                Integer::from(1),
+                Signature::new(Integer::from(0), Integer::from(1)),
+                0
+            ),
+            Err(Error::InvalidSignature)
+        );
+        assert_eq!(
+            Secp256k1::recover(
+                Integer::from(1),
+                Signature::new(Integer::from(1), Secp256k1::get_n()),
+                0
+            ),
+            Err(Error::InvalidSignature)
+        );
+    }
+
     #[test]
     fn test_sec() {
         let private_key_1 = Integer::from(5000);
@@ -398,28 +817,106 @@ mod tests {
         );
 
         assert_eq!(
-            Secp256k1::parse_sec(&sec256_1.sec(false)).public_key,
+            Secp256k1::parse_sec(&sec256_1.sec(false))
+                .unwrap()
+                .public_key,
             sec256_1.public_key
         );
         assert_eq!(
-            Secp256k1::parse_sec(&sec256_2.sec(false)).public_key,
+            Secp256k1::parse_sec(&sec256_2.sec(false))
+                .unwrap()
+                .public_key,
             sec256_2.public_key
         );
         assert_eq!(
-            Secp256k1::parse_sec(&sec256_3.sec(false)).public_key,
+            Secp256k1::parse_sec(&sec256_3.sec(false))
+                .unwrap()
+                .public_key,
             sec256_3.public_key
         );
         assert_eq!(
-            Secp256k1::parse_sec(&sec256_4.sec(true)).public_key,
+            Secp256k1::parse_sec(&sec256_4.sec(true))
+                .unwrap()
+                .public_key,
             sec256_4.public_key
         );
         assert_eq!(
-            Secp256k1::parse_sec(&sec256_5.sec(true)).public_key,
+            Secp256k1::parse_sec(&sec256_5.sec(true))
+                .unwrap()
+                .public_key,
             sec256_5.public_key
         );
         assert_eq!(
-            Secp256k1::parse_sec(&sec256_6.sec(true)).public_key,
+            Secp256k1::parse_sec(&sec256_6.sec(true))
+                .unwrap()
+                .public_key,
             sec256_6.public_key
         );
     }
+
+    #[test]
+    fn test_parse_sec_invalid() {
+        let sec256 = Secp256k1::generate_key_pair_from_secret("my secret");
+        let compressed = sec256.sec(true);
+        let uncompressed = sec256.sec(false);
+
+        assert_eq!(
+            Secp256k1::parse_sec(&[]),
+            Err(Error::InvalidSecLength {
+                expected: 33,
+                actual: 0
+            })
+        );
+        assert_eq!(
+            Secp256k1::parse_sec(&compressed[..32]),
+            Err(Error::InvalidSecLength {
+                expected: 33,
+                actual: 32
+            })
+        );
+        assert_eq!(
+            Secp256k1::parse_sec(&uncompressed[..64]),
+            Err(Error::InvalidSecLength {
+                expected: 65,
+                actual: 64
+            })
+        );
+        assert_eq!(
+            Secp256k1::parse_sec(&[&[0x05], &compressed[1..]].concat()),
+            Err(Error::InvalidSecPrefix(0x05))
+        );
+
+        // y座標を改ざんした点は曲線上にない
+        let mut tampered = uncompressed;
+        tampered[64] ^= 0x01;
+        assert!(matches!(
+            Secp256k1::parse_sec(&tampered),
+            Err(Error::EllipticCurve(
+                elliptic_curve::Error::NotOnCurve { .. }
+            ))
+        ));
+
+        // x = p は有限体の範囲外
+        let mut out_of_range = vec![0x02];
+        out_of_range.extend(Secp256k1::get_p().to_digits::<u8>(Order::MsfBe));
+        assert!(matches!(
+            Secp256k1::parse_sec(&out_of_range),
+            Err(Error::FieldElement(field_element::Error::OutOfRange { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_sign_without_private_key() {
+        let sec256 = Secp256k1::generate_key_pair_from_secret("my secret");
+        let public_only = Secp256k1::new(None, sec256.public_key.clone());
+
+        assert_eq!(
+            public_only.sign(Integer::from(1), Integer::from(1)),
+            Err(Error::MissingPrivateKey)
+        );
+        assert_eq!(
+            public_only.deterministic_k(Integer::from(1)),
+            Err(Error::MissingPrivateKey)
+        );
+    }
 }
@@ -0,0 +1,79 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use rug::Integer;
+use zeroize::Zeroize;
+
+/// GMPが確保したリムを0で上書きしてから値を0にする
+pub(crate) fn zeroize_integer(num: &mut Integer) {
+    // SAFETY: alloc 個のリムは d が指す領域に確保されており、上書きしても size を
+    // 0 にするまで値として読まれることはない
+    unsafe {
+        let raw = num.as_raw_mut();
+        let limbs = std::slice::from_raw_parts_mut((*raw).d.as_ptr(), (*raw).alloc as usize);
+        limbs.zeroize();
+        (*raw).size = 0;
+    }
+}
+
+/// drop時にリムを0で上書きする秘密の整数 (秘密鍵やnonce)
+pub(crate) struct SecretInteger(Integer);
+
+impl SecretInteger {
+    pub(crate) fn new(num: Integer) -> Self {
+        SecretInteger(num)
+    }
+}
+
+impl Deref for SecretInteger {
+    type Target = Integer;
+
+    fn deref(&self) -> &Integer {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretInteger {
+    fn deref_mut(&mut self) -> &mut Integer {
+        &mut self.0
+    }
+}
+
+impl Drop for SecretInteger {
+    fn drop(&mut self) {
+        zeroize_integer(&mut self.0);
+    }
+}
+
+/// `Debug` で秘密の値の代わりに表示する
+pub(crate) struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize_integer() {
+        let mut num = Integer::from_str_radix("deadbeef".repeat(8).as_str(), 16).unwrap();
+        zeroize_integer(&mut num);
+        assert_eq!(num, 0);
+
+        // 確保済みの領域も0で上書きされている
+        let raw = num.as_raw();
+        let limbs = unsafe { std::slice::from_raw_parts((*raw).d.as_ptr(), (*raw).alloc as usize) };
+        assert!(limbs.iter().all(|&limb| limb == 0));
+
+        let mut secret = SecretInteger::new(Integer::from(12345));
+        *secret += 1;
+        assert_eq!(*secret, 12346);
+        assert_eq!(format!("{:?}", Redacted), "<redacted>");
+    }
+}
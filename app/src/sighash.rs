@@ -0,0 +1,1071 @@
+use elliptic_curve::Ecdsa;
+use rug::{integer::Order, Integer};
+
+use crate::{
+    hash::{create_hash160, create_hash256, create_sha256, create_tagged_hash},
+    script::push_data,
+    secp256k1::Secp256k1,
+    tx::{encode_varint, Tx, TxOut},
+    Error,
+};
+
+/// すべての入力と出力に署名する
+pub const SIGHASH_ALL: u32 = 0x01;
+/// 出力には署名しない
+pub const SIGHASH_NONE: u32 = 0x02;
+/// 入力と同じ位置の出力にだけ署名する
+pub const SIGHASH_SINGLE: u32 = 0x03;
+/// 他のフラグと組み合わせ、署名する入力以外を対象から外す
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+/// Taproot で sighash type を省略した署名。SIGHASH_ALL と同じ範囲に署名する
+pub const SIGHASH_DEFAULT: u32 = 0x00;
+
+/// Taproot の annex の先頭バイト
+const ANNEX_TAG: u8 = 0x50;
+
+impl Tx {
+    /// レガシーな入力の署名ハッシュを求める
+    ///
+    /// `script_code` は使用する出力の scriptPubKey (P2SHなら redeemScript)
+    pub fn sig_hash_legacy(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        sighash_type: u32,
+    ) -> Result<[u8; 32], Error> {
+        if input_index >= self.tx_ins.len() {
+            return Err(Error::InvalidTransaction("input index out of range"));
+        }
+
+        let base_type = sighash_type & 0x1f;
+        // 対応する出力がない SIGHASH_SINGLE は、Bitcoin Core の実装どおり 1 に署名する
+        if base_type == SIGHASH_SINGLE && input_index >= self.tx_outs.len() {
+            let mut one = [0u8; 32];
+            one[0] = 0x01;
+            return Ok(one);
+        }
+
+        let mut tx = self.clone();
+        for (i, tx_in) in tx.tx_ins.iter_mut().enumerate() {
+            tx_in.witness.clear();
+            if i == input_index {
+                tx_in.script_sig = script_code.to_vec();
+            } else {
+                tx_in.script_sig.clear();
+                // NONE と SINGLE では他の入力の sequence は自由に変えられる
+                if base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE {
+                    tx_in.sequence = 0;
+                }
+            }
+        }
+
+        match base_type {
+            SIGHASH_NONE => tx.tx_outs.clear(),
+            SIGHASH_SINGLE => {
+                tx.tx_outs.truncate(input_index + 1);
+                for tx_out in tx.tx_outs.iter_mut().take(input_index) {
+                    *tx_out = TxOut {
+                        amount: u64::MAX,
+                        script_pubkey: vec![],
+                    };
+                }
+            }
+            _ => {}
+        }
+
+        if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+            tx.tx_ins = vec![tx.tx_ins.swap_remove(input_index)];
+        }
+
+        let mut preimage = tx.serialize_legacy();
+        preimage.extend(sighash_type.to_le_bytes());
+        Ok(hash256(&preimage))
+    }
+
+    /// P2PKH の入力に署名し、scriptSig を <DER署名 + sighash type> <SEC公開鍵> にする
+    pub fn sign_input_p2pkh(
+        &mut self,
+        input_index: usize,
+        script_pubkey: &[u8],
+        key: &Secp256k1,
+        compressed: bool,
+        sighash_type: u32,
+    ) -> Result<(), Error> {
+        let z = self.sig_hash_legacy(input_index, script_pubkey, sighash_type)?;
+        let sig = ecdsa_signature(&z, key, sighash_type)?;
+
+        let mut script_sig = vec![];
+        push_data(&mut script_sig, &sig);
+        push_data(&mut script_sig, &key.sec(compressed));
+        self.tx_ins[input_index].script_sig = script_sig;
+        Ok(())
+    }
+}
+
+/// BIP143 の署名ハッシュで入力をまたいで共通の hashPrevouts, hashSequence, hashOutputs
+///
+/// 入力ごとに計算し直すと入力数の2乗の手間になるので、一度だけ求めて使い回す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bip143Cache<'a> {
+    tx: &'a Tx,
+    pub hash_prevouts: [u8; 32],
+    pub hash_sequence: [u8; 32],
+    pub hash_outputs: [u8; 32],
+}
+
+impl<'a> Bip143Cache<'a> {
+    pub fn new(tx: &'a Tx) -> Self {
+        let mut prevouts = vec![];
+        let mut sequences = vec![];
+        for tx_in in &tx.tx_ins {
+            prevouts.extend(tx_in.prev_tx.iter().rev());
+            prevouts.extend(tx_in.prev_index.to_le_bytes());
+            sequences.extend(tx_in.sequence.to_le_bytes());
+        }
+        let outputs: Vec<u8> = tx.tx_outs.iter().flat_map(TxOut::serialize).collect();
+
+        Bip143Cache {
+            tx,
+            hash_prevouts: hash256(&prevouts),
+            hash_sequence: hash256(&sequences),
+            hash_outputs: hash256(&outputs),
+        }
+    }
+
+    /// SegWit v0 の入力の署名ハッシュを求める
+    ///
+    /// `script_code` は P2WPKH なら P2PKH 形式のスクリプト、P2WSH なら witnessScript。
+    /// 使用する出力の金額 `amount` も署名の対象になる
+    pub fn sig_hash(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        amount: u64,
+        sighash_type: u32,
+    ) -> Result<[u8; 32], Error> {
+        let tx = self.tx;
+        let tx_in = tx
+            .tx_ins
+            .get(input_index)
+            .ok_or(Error::InvalidTransaction("input index out of range"))?;
+
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+        let hash_prevouts = if anyone_can_pay {
+            [0u8; 32]
+        } else {
+            self.hash_prevouts
+        };
+        let hash_sequence =
+            if anyone_can_pay || base_type == SIGHASH_SINGLE || base_type == SIGHASH_NONE {
+                [0u8; 32]
+            } else {
+                self.hash_sequence
+            };
+        let hash_outputs = match base_type {
+            SIGHASH_SINGLE if input_index < tx.tx_outs.len() => {
+                hash256(&tx.tx_outs[input_index].serialize())
+            }
+            SIGHASH_SINGLE | SIGHASH_NONE => [0u8; 32],
+            _ => self.hash_outputs,
+        };
+
+        let mut preimage = tx.version.to_le_bytes().to_vec();
+        preimage.extend(hash_prevouts);
+        preimage.extend(hash_sequence);
+        preimage.extend(tx_in.prev_tx.iter().rev());
+        preimage.extend(tx_in.prev_index.to_le_bytes());
+        preimage.extend(encode_varint(script_code.len() as u64));
+        preimage.extend(script_code);
+        preimage.extend(amount.to_le_bytes());
+        preimage.extend(tx_in.sequence.to_le_bytes());
+        preimage.extend(hash_outputs);
+        preimage.extend(tx.locktime.to_le_bytes());
+        preimage.extend(sighash_type.to_le_bytes());
+        Ok(hash256(&preimage))
+    }
+}
+
+impl Tx {
+    /// SegWit v0 の入力の署名ハッシュを求める (BIP143)
+    ///
+    /// 複数の入力に署名するときは `Bip143Cache` を使い回した方がよい
+    pub fn sig_hash_segwit_v0(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        amount: u64,
+        sighash_type: u32,
+    ) -> Result<[u8; 32], Error> {
+        Bip143Cache::new(self).sig_hash(input_index, script_code, amount, sighash_type)
+    }
+
+    /// SegWit v0 の入力に対する、DER署名の末尾に sighash type を付けたバイト列
+    pub fn segwit_v0_signature(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        amount: u64,
+        key: &Secp256k1,
+        sighash_type: u32,
+    ) -> Result<Vec<u8>, Error> {
+        let z = self.sig_hash_segwit_v0(input_index, script_code, amount, sighash_type)?;
+        ecdsa_signature(&z, key, sighash_type)
+    }
+
+    /// P2WPKH の入力に署名し、witness を <署名> <圧縮公開鍵> にする
+    pub fn sign_input_p2wpkh(
+        &mut self,
+        input_index: usize,
+        amount: u64,
+        key: &Secp256k1,
+        sighash_type: u32,
+    ) -> Result<(), Error> {
+        let sec = key.sec(true);
+        let script_code = p2pkh_script_code(&create_hash160(&sec));
+        let sig = self.segwit_v0_signature(input_index, &script_code, amount, key, sighash_type)?;
+
+        let tx_in = &mut self.tx_ins[input_index];
+        tx_in.script_sig.clear();
+        tx_in.witness = vec![sig, sec];
+        Ok(())
+    }
+
+    /// `<公開鍵> OP_CHECKSIG` のように署名を1つ取る P2WSH の入力に署名し、
+    /// witness を <署名> <witnessScript> にする
+    ///
+    /// マルチシグなど他の形の witness は `segwit_v0_signature` で署名を作って組み立てる
+    pub fn sign_input_p2wsh(
+        &mut self,
+        input_index: usize,
+        witness_script: &[u8],
+        amount: u64,
+        key: &Secp256k1,
+        sighash_type: u32,
+    ) -> Result<(), Error> {
+        let sig =
+            self.segwit_v0_signature(input_index, witness_script, amount, key, sighash_type)?;
+
+        let tx_in = &mut self.tx_ins[input_index];
+        tx_in.script_sig.clear();
+        tx_in.witness = vec![sig, witness_script.to_vec()];
+        Ok(())
+    }
+}
+
+/// BIP341 の署名メッセージで入力をまたいで共通の SHA256
+///
+/// Taproot ではすべての入力が使う出力の金額と scriptPubKey も署名の対象になる
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootCache<'a> {
+    tx: &'a Tx,
+    prevouts: &'a [TxOut],
+    pub sha_prevouts: [u8; 32],
+    pub sha_amounts: [u8; 32],
+    pub sha_script_pubkeys: [u8; 32],
+    pub sha_sequences: [u8; 32],
+    pub sha_outputs: [u8; 32],
+}
+
+impl<'a> TaprootCache<'a> {
+    /// `prevouts` は各入力が使う出力で、入力と同じ順に並べる
+    pub fn new(tx: &'a Tx, prevouts: &'a [TxOut]) -> Result<Self, Error> {
+        if prevouts.len() != tx.tx_ins.len() {
+            return Err(Error::InvalidTransaction(
+                "number of prevouts does not match inputs",
+            ));
+        }
+
+        let mut outpoints = vec![];
+        let mut sequences = vec![];
+        for tx_in in &tx.tx_ins {
+            outpoints.extend(tx_in.prev_tx.iter().rev());
+            outpoints.extend(tx_in.prev_index.to_le_bytes());
+            sequences.extend(tx_in.sequence.to_le_bytes());
+        }
+        let mut amounts = vec![];
+        let mut script_pubkeys = vec![];
+        for prevout in prevouts {
+            amounts.extend(prevout.amount.to_le_bytes());
+            script_pubkeys.extend(encode_varint(prevout.script_pubkey.len() as u64));
+            script_pubkeys.extend(&prevout.script_pubkey);
+        }
+        let outputs: Vec<u8> = tx.tx_outs.iter().flat_map(TxOut::serialize).collect();
+
+        Ok(TaprootCache {
+            tx,
+            prevouts,
+            sha_prevouts: sha256(&outpoints),
+            sha_amounts: sha256(&amounts),
+            sha_script_pubkeys: sha256(&script_pubkeys),
+            sha_sequences: sha256(&sequences),
+            sha_outputs: sha256(&outputs),
+        })
+    }
+
+    /// Taproot の入力の署名ハッシュ hash_TapSighash(0x00 || SigMsg) を求める
+    ///
+    /// `annex` は witness の最後の 0x50 で始まる要素。
+    /// `leaf_hash` を渡すと script path (BIP342) の署名ハッシュになる
+    pub fn sig_hash(
+        &self,
+        input_index: usize,
+        sighash_type: u32,
+        annex: Option<&[u8]>,
+        leaf_hash: Option<&[u8; 32]>,
+    ) -> Result<[u8; 32], Error> {
+        if !matches!(sighash_type, 0x00..=0x03 | 0x81..=0x83) {
+            return Err(Error::InvalidTransaction("invalid taproot sighash type"));
+        }
+        let tx = self.tx;
+        let tx_in = tx
+            .tx_ins
+            .get(input_index)
+            .ok_or(Error::InvalidTransaction("input index out of range"))?;
+
+        let base_type = sighash_type & 0x03;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+        // 先頭の 0x00 はエポック
+        let mut msg = vec![0x00, sighash_type as u8];
+        msg.extend(tx.version.to_le_bytes());
+        msg.extend(tx.locktime.to_le_bytes());
+        if !anyone_can_pay {
+            msg.extend(self.sha_prevouts);
+            msg.extend(self.sha_amounts);
+            msg.extend(self.sha_script_pubkeys);
+            msg.extend(self.sha_sequences);
+        }
+        if base_type != SIGHASH_NONE && base_type != SIGHASH_SINGLE {
+            msg.extend(self.sha_outputs);
+        }
+
+        let spend_type = (u8::from(leaf_hash.is_some()) << 1) | u8::from(annex.is_some());
+        msg.push(spend_type);
+        if anyone_can_pay {
+            let prevout = &self.prevouts[input_index];
+            msg.extend(tx_in.prev_tx.iter().rev());
+            msg.extend(tx_in.prev_index.to_le_bytes());
+            msg.extend(prevout.amount.to_le_bytes());
+            msg.extend(encode_varint(prevout.script_pubkey.len() as u64));
+            msg.extend(&prevout.script_pubkey);
+            msg.extend(tx_in.sequence.to_le_bytes());
+        } else {
+            msg.extend((input_index as u32).to_le_bytes());
+        }
+        if let Some(annex) = annex {
+            if annex.first() != Some(&ANNEX_TAG) {
+                return Err(Error::InvalidTransaction("annex must start with 0x50"));
+            }
+            msg.extend(sha256(
+                &[&encode_varint(annex.len() as u64)[..], annex].concat(),
+            ));
+        }
+        if base_type == SIGHASH_SINGLE {
+            let tx_out = tx
+                .tx_outs
+                .get(input_index)
+                .ok_or(Error::InvalidTransaction("no output for SIGHASH_SINGLE"))?;
+            msg.extend(sha256(&tx_out.serialize()));
+        }
+        if let Some(leaf_hash) = leaf_hash {
+            // key_version 0 と、OP_CODESEPARATOR を実行していないことを示す 0xffffffff
+            msg.extend(leaf_hash);
+            msg.push(0x00);
+            msg.extend(u32::MAX.to_le_bytes());
+        }
+
+        Ok(create_tagged_hash("TapSighash", &msg).try_into().unwrap())
+    }
+}
+
+impl Tx {
+    /// Taproot の key path での署名ハッシュを求める (BIP341)
+    pub fn sig_hash_taproot(
+        &self,
+        input_index: usize,
+        prevouts: &[TxOut],
+        sighash_type: u32,
+    ) -> Result<[u8; 32], Error> {
+        TaprootCache::new(self, prevouts)?.sig_hash(input_index, sighash_type, None, None)
+    }
+
+    /// 内部鍵を TapTweak で調整した鍵で Taproot の入力に key path で署名する
+    ///
+    /// witness は64バイトの署名1つで、SIGHASH_DEFAULT 以外は末尾に sighash type を付ける
+    pub fn sign_input_taproot_key_path(
+        &mut self,
+        input_index: usize,
+        prevouts: &[TxOut],
+        internal_key: &Secp256k1,
+        merkle_root: Option<&[u8; 32]>,
+        sighash_type: u32,
+        aux_rand: &[u8; 32],
+    ) -> Result<(), Error> {
+        let msg = self.sig_hash_taproot(input_index, prevouts, sighash_type)?;
+        let output_key = internal_key.taproot_tweak_key(merkle_root)?;
+
+        let mut sig = output_key.schnorr_sign(&msg, aux_rand)?.to_vec();
+        if sighash_type != SIGHASH_DEFAULT {
+            sig.push(sighash_type as u8);
+        }
+
+        let tx_in = &mut self.tx_ins[input_index];
+        tx_in.script_sig.clear();
+        tx_in.witness = vec![sig];
+        Ok(())
+    }
+}
+
+/// P2WPKH の scriptCode (OP_DUP OP_HASH160 <hash160> OP_EQUALVERIFY OP_CHECKSIG)
+fn p2pkh_script_code(hash160: &[u8]) -> Vec<u8> {
+    let mut script = vec![0x76, 0xa9];
+    push_data(&mut script, hash160);
+    script.extend([0x88, 0xac]);
+    script
+}
+
+/// RFC 6979 のnonceでECDSA署名し、DERの末尾に sighash type を付ける
+fn ecdsa_signature(z: &[u8; 32], key: &Secp256k1, sighash_type: u32) -> Result<Vec<u8>, Error> {
+    let z = Integer::from_digits(z, Order::MsfBe);
+    let k = key.deterministic_k(z.clone())?;
+    let mut sig = key.sign(z, k)?.der();
+    sig.push(sighash_type as u8);
+    Ok(sig)
+}
+
+fn hash256(bytes: &[u8]) -> [u8; 32] {
+    create_hash256(bytes).try_into().unwrap()
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    create_sha256(bytes).try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Signature;
+
+    use super::*;
+    use crate::tx::TxIn;
+
+    // Programming Bitcoin 5章のメインネットのトランザクションと、使用した出力の scriptPubKey
+    const LEGACY_TX: &str = "0100000001813f79011acb80925dfe69b3def355fe914bd1d96a3f5f71bf8303c6a989c7d1000000006b483045022100ed81ff192e75a3fd2304004dcadb746fa5e24c5031ccfcf21320b0277457c98f02207a986d955c6e0cb35d446a89d3f56100f4d7f67801c31967743a9c8e10615bed01210349fc4e631e3624a545de3f89f5d8684c7b8138bd94bdd531d2e213bf016b278afeffffff02a135ef01000000001976a914bc3b654dca7e56b04dca18f2566cdaf02e8d9ada88ac99c39800000000001976a9141c4bc762dd5423e332166702cb75f40df79fea1288ac19430600";
+    const PREV_SCRIPT_PUBKEY: &str = "76a914a802fc56c704ce87c42d7c92eb75e7896bdc41ae88ac";
+
+    fn p2pkh_script(key: &Secp256k1, compressed: bool) -> Vec<u8> {
+        p2pkh_script_code(&create_hash160(&key.sec(compressed)))
+    }
+
+    /// scriptSig の <署名> <公開鍵> で、その入力の署名ハッシュを検証する
+    fn verify_p2pkh(tx: &Tx, input_index: usize, script_pubkey: &[u8]) -> bool {
+        let script_sig = &tx.tx_ins[input_index].script_sig;
+        let sig_len = script_sig[0] as usize;
+        let (der, sighash_type) = (&script_sig[1..sig_len], u32::from(script_sig[sig_len]));
+        let sec = &script_sig[sig_len + 2..];
+
+        let z = tx
+            .sig_hash_legacy(input_index, script_pubkey, sighash_type)
+            .unwrap();
+        let key = Secp256k1::parse_sec(sec).unwrap();
+        key.verify(
+            Integer::from_digits(&z, Order::MsfBe),
+            Signature::parse_der(der).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_sig_hash_legacy() {
+        let tx = Tx::parse(&hex::decode(LEGACY_TX).unwrap()).unwrap();
+        let script_pubkey = hex::decode(PREV_SCRIPT_PUBKEY).unwrap();
+
+        assert_eq!(
+            hex::encode(tx.sig_hash_legacy(0, &script_pubkey, SIGHASH_ALL).unwrap()),
+            "27e0c5994dec7824e56dec6b2fcb342eb7cdb0d0957c2fce9882f715e85d81a6"
+        );
+        assert!(verify_p2pkh(&tx, 0, &script_pubkey));
+
+        assert_eq!(
+            tx.sig_hash_legacy(1, &script_pubkey, SIGHASH_ALL),
+            Err(Error::InvalidTransaction("input index out of range"))
+        );
+    }
+
+    #[test]
+    fn test_sign_input_p2pkh() {
+        let key = Secp256k1::from_private_key(Integer::from(8675309)).unwrap();
+        let script_pubkey = p2pkh_script(&key, true);
+
+        let mut tx = Tx {
+            version: 1,
+            tx_ins: (0..3).map(|i| TxIn::new([i as u8; 32], i)).collect(),
+            tx_outs: (0..2)
+                .map(|i| TxOut {
+                    amount: 1000 * (i + 1),
+                    script_pubkey: script_pubkey.clone(),
+                })
+                .collect(),
+            locktime: 0,
+        };
+
+        let mut hashes = vec![];
+        for sighash_type in [
+            SIGHASH_ALL,
+            SIGHASH_NONE,
+            SIGHASH_SINGLE,
+            SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+            SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+            SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+        ] {
+            tx.sign_input_p2pkh(1, &script_pubkey, &key, true, sighash_type)
+                .unwrap();
+            assert!(verify_p2pkh(&tx, 1, &script_pubkey));
+            hashes.push(tx.sig_hash_legacy(1, &script_pubkey, sighash_type).unwrap());
+
+            // 署名の対象外の部分を変えても署名は有効なまま
+            let base_type = sighash_type & 0x1f;
+            let mut modified = tx.clone();
+            if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+                modified.tx_ins[0].prev_index = 100;
+            }
+            if base_type != SIGHASH_ALL {
+                modified.tx_ins[2].sequence = 0;
+                modified.tx_outs[0].amount += 1;
+            }
+            if base_type == SIGHASH_NONE {
+                modified.tx_outs[1].amount = 1;
+            }
+            assert!(verify_p2pkh(&modified, 1, &script_pubkey));
+
+            // 入力と同じ位置の出力は NONE 以外で署名の対象になる
+            let mut modified = tx.clone();
+            modified.tx_outs[1].amount += 1;
+            assert_eq!(
+                verify_p2pkh(&modified, 1, &script_pubkey),
+                base_type == SIGHASH_NONE
+            );
+        }
+        hashes.sort();
+        hashes.dedup();
+        assert_eq!(hashes.len(), 6);
+
+        // 対応する出力がない SIGHASH_SINGLE
+        let mut one = [0u8; 32];
+        one[0] = 0x01;
+        assert_eq!(
+            tx.sig_hash_legacy(2, &script_pubkey, SIGHASH_SINGLE),
+            Ok(one)
+        );
+
+        // 非圧縮の公開鍵
+        let script_pubkey = p2pkh_script(&key, false);
+        tx.sign_input_p2pkh(0, &script_pubkey, &key, false, SIGHASH_ALL)
+            .unwrap();
+        assert_eq!(tx.tx_ins[0].script_sig.last(), key.sec(false).last());
+        assert!(verify_p2pkh(&tx, 0, &script_pubkey));
+    }
+
+    fn key(hex: &str) -> Secp256k1 {
+        Secp256k1::from_private_key(Integer::from_str_radix(hex, 16).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_bip143_native_p2wpkh() {
+        let mut tx = Tx::parse(&hex::decode("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000").unwrap()).unwrap();
+        let key = key("619c335025c7f4012e556c2a58b2506e30b8511b53ade95ea316fd8c3286feb9");
+        let amount = 600000000;
+        let script_code = p2pkh_script(&key, true);
+        assert_eq!(
+            hex::encode(&script_code),
+            "76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac"
+        );
+
+        let cache = Bip143Cache::new(&tx);
+        assert_eq!(
+            hex::encode(cache.hash_prevouts),
+            "96b827c8483d4e9b96712b6713a7b68d6e8003a781feba36c31143470b4efd37"
+        );
+        assert_eq!(
+            hex::encode(cache.hash_sequence),
+            "52b0a642eea2fb7ae638c36f6252b6750293dbe574a806984b8e4d8548339a3b"
+        );
+        assert_eq!(
+            hex::encode(cache.hash_outputs),
+            "863ef3e1a92afbfdb97f31ad0fc7683ee943e9abcf2501590ff8f6551f47e5e5"
+        );
+        assert_eq!(
+            hex::encode(
+                cache
+                    .sig_hash(1, &script_code, amount, SIGHASH_ALL)
+                    .unwrap()
+            ),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+
+        tx.sign_input_p2wpkh(1, amount, &key, SIGHASH_ALL).unwrap();
+        assert!(tx.tx_ins[1].script_sig.is_empty());
+        assert_eq!(
+            hex::encode(&tx.tx_ins[1].witness[0]),
+            "304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee01"
+        );
+        assert_eq!(
+            hex::encode(&tx.tx_ins[1].witness[1]),
+            "025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357"
+        );
+    }
+
+    #[test]
+    fn test_bip143_p2sh_p2wpkh() {
+        let tx = Tx::parse(&hex::decode("0100000001db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a54770100000000feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac92040000").unwrap()).unwrap();
+        let key = key("eb696a065ef48a2192da5b28b694f87544b30fae8327c4510137a922f32c6dcf");
+        let script_code = p2pkh_script(&key, true);
+        assert_eq!(
+            hex::encode(&script_code),
+            "76a91479091972186c449eb1ded22b78e40d009bdf008988ac"
+        );
+
+        let cache = Bip143Cache::new(&tx);
+        assert_eq!(
+            hex::encode(cache.hash_prevouts),
+            "b0287b4a252ac05af83d2dcef00ba313af78a3e9c329afa216eb3aa2a7b4613a"
+        );
+        assert_eq!(
+            hex::encode(cache.hash_sequence),
+            "18606b350cd8bf565266bc352f0caddcf01e8fa789dd8a15386327cf8cabe198"
+        );
+        assert_eq!(
+            hex::encode(cache.hash_outputs),
+            "de984f44532e2173ca0d64314fcefe6d30da6f8cf27bafa706da61df8a226c83"
+        );
+        assert_eq!(
+            hex::encode(
+                tx.sig_hash_segwit_v0(0, &script_code, 1000000000, SIGHASH_ALL)
+                    .unwrap()
+            ),
+            "64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6"
+        );
+    }
+
+    /// witness の署名 (末尾に sighash type 付き) を、その署名ハッシュと公開鍵で検証する
+    fn verify_segwit_sig(key: &Secp256k1, z: &[u8; 32], sig: &[u8]) -> bool {
+        key.verify(
+            Integer::from_digits(z, Order::MsfBe),
+            Signature::parse_der(&sig[..sig.len() - 1]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_bip143_native_p2wsh() {
+        // <026dcc..> OP_CHECKSIGVERIFY OP_CODESEPARATOR <0255a9..> OP_CHECKSIG
+        let tx = Tx::parse(&hex::decode("0100000002fe3dc9208094f3ffd12645477b3dc56f60ec4fa8e6f5d67c565d1c6b9216b36e0000000000ffffffff0815cf020f013ed6cf91d29f4202e8a58726b1ac6c79da47c23d1bee0a6925f80000000000ffffffff0100f2052a010000001976a914a30741f8145e5acadf23f751864167f32e0963f788ac00000000").unwrap()).unwrap();
+        let witness_script = hex::decode("21026dccc749adc2a9d0d89497ac511f760f45c47dc5ed9cf352a58ac706453880aeadab210255a9626aebf5e29c0e6538428ba0d1dcf6ca98ffdf086aa8ced5e0d0215ea465ac").unwrap();
+        assert_eq!(
+            hex::encode(sha256(&witness_script)),
+            "5d1b56b63d714eebe542309525f484b7e9d6f686b3781b6f61ef925d66d6f6a0"
+        );
+        let amount = 4900000000;
+        let key1 = key("8e02b539b1500aa7c81cf3fed177448a546f19d2be416c0c61ff28e577d8d0cd");
+        let key2 = key("86bf2ed75935a0cbef03b89d72034bb4c189d381037a5ac121a70016db8896ec");
+        assert_eq!(key1.sec(true), witness_script[1..34]);
+        assert_eq!(key2.sec(true), witness_script[37..70]);
+
+        // 1つ目の署名は witnessScript 全体、2つ目は OP_CODESEPARATOR 以降を scriptCode にする
+        let z1 = tx
+            .sig_hash_segwit_v0(1, &witness_script, amount, SIGHASH_SINGLE)
+            .unwrap();
+        assert_eq!(
+            hex::encode(z1),
+            "82dde6e4f1e94d02c2b7ad03d2115d691f48d064e9d52f58194a6637e4194391"
+        );
+        let z2 = tx
+            .sig_hash_segwit_v0(1, &witness_script[36..], amount, SIGHASH_SINGLE)
+            .unwrap();
+        assert_eq!(
+            hex::encode(z2),
+            "fef7bd749cce710c5c052bd796df1af0d935e59cea63736268bcbe2d2134fc47"
+        );
+
+        // BIP143 に載っている署名済みトランザクションの witness
+        let sig1 = hex::decode("3044022027dc95ad6b740fe5129e7e62a75dd00f291a2aeb1200b84b09d9e3789406b6c002201a9ecd315dd6a0e632ab20bbb98948bc0c6fb204f2c286963bb48517a7058e2703").unwrap();
+        let sig2 = hex::decode("304402200de66acf4527789bfda55fc5459e214fa6083f936b430a762c629656216805ac0220396f550692cd347171cbc1ef1f51e15282e837bb2b30860dc77c8f78bc8501e503").unwrap();
+        assert!(verify_segwit_sig(&key1, &z1, &sig1));
+        assert!(verify_segwit_sig(&key2, &z2, &sig2));
+        assert!(!verify_segwit_sig(&key2, &z1, &sig2));
+    }
+
+    #[test]
+    fn test_bip143_p2sh_p2wsh() {
+        // 6-of-6 のマルチシグで、鍵ごとに異なる sighash type で署名する
+        let tx = Tx::parse(&hex::decode("010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e0100000000ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac00000000").unwrap()).unwrap();
+        let witness_script = hex::decode("56210307b8ae49ac90a048e9b53357a2354b3334e9c8bee813ecb98e99a7e07e8c3ba32103b28f0c28bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b21034b8113d703413d57761b8b9781957b8c0ac1dfe69f492580ca4195f50376ba4a21033400f6afecb833092a9a21cfdf1ed1376e58c5d1f47de74683123987e967a8f42103a6d48b1131e94ba04d9737d61acdaa1322008af9602b3b14862c07a1789aac162102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b56ae").unwrap();
+        let redeem_script = [&[0x00, 0x20][..], &sha256(&witness_script)].concat();
+        assert_eq!(
+            hex::encode(&redeem_script),
+            "0020a16b5755f7f6f96dbd65f5f0d6ab9418b89af4b1f14a1bb8a09062c35f0dcb54"
+        );
+        assert_eq!(
+            hex::encode(create_hash160(&redeem_script)),
+            "9993a429037b5d912407a71c252019287b8d27a5"
+        );
+        let amount = 987654321;
+
+        let cache = Bip143Cache::new(&tx);
+        for (i, (secret, sighash_type, expected)) in [
+            (
+                "730fff80e1413068a05b57d6a58261f07551163369787f349438ea38ca80fac6",
+                SIGHASH_ALL,
+                "185c0be5263dce5b4bb50a047973c1b6272bfbd0103a89444597dc40b248ee7c",
+            ),
+            (
+                "11fa3d25a17cbc22b29c44a484ba552b5a53149d106d3d853e22fdd05a2d8bb3",
+                SIGHASH_NONE,
+                "e9733bc60ea13c95c6527066bb975a2ff29a925e80aa14c213f686cbae5d2f36",
+            ),
+            (
+                "77bf4141a87d55bdd7f3cd0bdccf6e9e642935fec45f2f30047be7b799120661",
+                SIGHASH_SINGLE,
+                "1e1f1c303dc025bd664acb72e583e933fae4cff9148bf78c157d1e8f78530aea",
+            ),
+            (
+                "14af36970f5025ea3e8b5542c0f8ebe7763e674838d08808896b63c3351ffe49",
+                SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+                "2a67f03e63a6a422125878b40b82da593be8d4efaafe88ee528af6e5a9955c6e",
+            ),
+            (
+                "fe9a95c19eef81dde2b95c1284ef39be497d128e2aa46916fb02d552485e0323",
+                SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+                "781ba15f3779d5542ce8ecb5c18716733a5ee42a6f51488ec96154934e2c890a",
+            ),
+            (
+                "428a7aee9f0c2af0cd19af3cf1c78149951ea528726989b2e83e4778d2c3f890",
+                SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+                "511e8e52ed574121fc1b654970395502128263f62662e076dc6baf05c2e6a99b",
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let key = key(secret);
+            // witnessScript の i 番目の公開鍵
+            let offset = 2 + 34 * i;
+            assert_eq!(key.sec(true), witness_script[offset..offset + 33]);
+
+            let z = cache
+                .sig_hash(0, &witness_script, amount, sighash_type)
+                .unwrap();
+            assert_eq!(hex::encode(z), expected);
+
+            let sig = tx
+                .segwit_v0_signature(0, &witness_script, amount, &key, sighash_type)
+                .unwrap();
+            assert_eq!(u32::from(*sig.last().unwrap()), sighash_type);
+            assert!(verify_segwit_sig(&key, &z, &sig));
+        }
+    }
+
+    #[test]
+    fn test_sign_input_p2wsh() {
+        let key = Secp256k1::from_private_key(Integer::from(8675309)).unwrap();
+        // <公開鍵> OP_CHECKSIG
+        let mut witness_script = vec![];
+        push_data(&mut witness_script, &key.sec(true));
+        witness_script.push(0xac);
+
+        let mut tx = Tx {
+            version: 2,
+            tx_ins: (0..2).map(|i| TxIn::new([0xaa; 32], i)).collect(),
+            tx_outs: (0..2)
+                .map(|i| TxOut {
+                    amount: 5000 * (i + 1),
+                    script_pubkey: vec![0x00, 0x14, i as u8],
+                })
+                .collect(),
+            locktime: 0,
+        };
+
+        for sighash_type in [
+            SIGHASH_ALL,
+            SIGHASH_NONE,
+            SIGHASH_SINGLE,
+            SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+            SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+            SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+        ] {
+            tx.sign_input_p2wsh(1, &witness_script, 20000, &key, sighash_type)
+                .unwrap();
+            let witness = &tx.tx_ins[1].witness;
+            assert_eq!(witness[1], witness_script);
+            assert_eq!(*witness[0].last().unwrap() as u32, sighash_type);
+
+            let der = &witness[0][..witness[0].len() - 1];
+            let verify = |tx: &Tx, amount: u64| {
+                let z = tx
+                    .sig_hash_segwit_v0(1, &witness_script, amount, sighash_type)
+                    .unwrap();
+                key.verify(
+                    Integer::from_digits(&z, Order::MsfBe),
+                    Signature::parse_der(der).unwrap(),
+                )
+            };
+            assert!(verify(&tx, 20000));
+            // 金額も署名の対象になる
+            assert!(!verify(&tx, 20001));
+
+            let mut modified = tx.clone();
+            modified.tx_ins[0].sequence = 0;
+            assert_eq!(verify(&modified, 20000), sighash_type != SIGHASH_ALL);
+        }
+        assert_eq!(
+            tx.sig_hash_segwit_v0(2, &witness_script, 0, SIGHASH_ALL),
+            Err(Error::InvalidTransaction("input index out of range"))
+        );
+    }
+
+    #[test]
+    fn test_bip341_key_path_spending() {
+        // BIP341 wallet test vectors (keyPathSpending)
+        let tx = Tx::parse(&hex::decode("02000000097de20cbff686da83a54981d2b9bab3586f4ca7e48f57f5b55963115f3b334e9c010000000000000000d7b7cab57b1393ace2d064f4d4a2cb8af6def61273e127517d44759b6dafdd990000000000fffffffff8e1f583384333689228c5d28eac13366be082dc57441760d957275419a418420000000000fffffffff0689180aa63b30cb162a73c6d2a38b7eeda2a83ece74310fda0843ad604853b0100000000feffffffaa5202bdf6d8ccd2ee0f0202afbbb7461d9264a25e5bfd3c5a52ee1239e0ba6c0000000000feffffff956149bdc66faa968eb2be2d2faa29718acbfe3941215893a2a3446d32acd050000000000000000000e664b9773b88c09c32cb70a2a3e4da0ced63b7ba3b22f848531bbb1d5d5f4c94010000000000000000e9aa6b8e6c9de67619e6a3924ae25696bb7b694bb677a632a74ef7eadfd4eabf0000000000ffffffffa778eb6a263dc090464cd125c466b5a99667720b1c110468831d058aa1b82af10100000000ffffffff0200ca9a3b000000001976a91406afd46bcdfd22ef94ac122aa11f241244a37ecc88ac807840cb0000000020ac9a87f5594be208f8532db38cff670c450ed2fea8fcdefcc9a663f78bab962b0065cd1d").unwrap()).unwrap();
+        let prevouts: Vec<TxOut> = [
+            (
+                "512053a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+                420000000,
+            ),
+            (
+                "5120147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+                462000000,
+            ),
+            (
+                "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac",
+                294000000,
+            ),
+            (
+                "5120e4d810fd50586274face62b8a807eb9719cef49c04177cc6b76a9a4251d5450e",
+                504000000,
+            ),
+            (
+                "512091b64d5324723a985170e4dc5a0f84c041804f2cd12660fa5dec09fc21783605",
+                630000000,
+            ),
+            ("00147dd65592d0ab2fe0d0257d571abf032cd9db93dc", 378000000),
+            (
+                "512075169f4001aa68f15bbed28b218df1d0a62cbbcf1188c6665110c293c907b831",
+                672000000,
+            ),
+            (
+                "5120712447206d7a5238acc7ff53fbe94a3b64539ad291c7cdbc490b7577e4b17df5",
+                546000000,
+            ),
+            (
+                "512077e30a5522dd9f894c3f8b8bd4c4b2cf82ca7da8a3ea6a239655c39c050ab220",
+                588000000,
+            ),
+        ]
+        .iter()
+        .map(|(script_pubkey, amount)| TxOut {
+            amount: *amount,
+            script_pubkey: hex::decode(script_pubkey).unwrap(),
+        })
+        .collect();
+
+        let cache = TaprootCache::new(&tx, &prevouts).unwrap();
+        assert_eq!(
+            hex::encode(cache.sha_prevouts),
+            "e3b33bb4ef3a52ad1fffb555c0d82828eb22737036eaeb02a235d82b909c4c3f"
+        );
+        assert_eq!(
+            hex::encode(cache.sha_amounts),
+            "58a6964a4f5f8f0b642ded0a8a553be7622a719da71d1f5befcefcdee8e0fde6"
+        );
+        assert_eq!(
+            hex::encode(cache.sha_script_pubkeys),
+            "23ad0f61ad2bca5ba6a7693f50fce988e17c3780bf2b1e720cfbb38fbdd52e21"
+        );
+        assert_eq!(
+            hex::encode(cache.sha_sequences),
+            "18959c7221ab5ce9e26c3cd67b22c24f8baa54bac281d8e6b05e400e6c3a957e"
+        );
+        assert_eq!(
+            hex::encode(cache.sha_outputs),
+            "a2e6dab7c1f0dcd297c8d61647fd17d821541ea69c3cc37dcbad7f90d4eb4bc5"
+        );
+        // 調整後の鍵が使う出力の scriptPubKey と一致し、aux_rand を0にした署名が witness と一致する
+        let mut signed = tx.clone();
+        for (index, secret, merkle_root, sighash_type, expected_sig_hash, expected_witness) in [
+            (
+                0,
+                "6b973d88838f27366ed61c9ad6367663045cb456e28335c109e30717ae0c6baa",
+                None,
+                SIGHASH_SINGLE,
+                "2514a6272f85cfa0f45eb907fcb0d121b808ed37c6ea160a5a9046ed5526d555",
+                "ed7c1647cb97379e76892be0cacff57ec4a7102aa24296ca39af7541246d8ff14d38958d4cc1e2e478e4d4a764bbfd835b16d4e314b72937b29833060b87276c03",
+            ),
+            (
+                1,
+                "1e4da49f6aaf4e5cd175fe08a32bb5cb4863d963921255f33d3bc31e1343907f",
+                Some("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"),
+                SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+                "325a644af47e8a5a2591cda0ab0723978537318f10e6a63d4eed783b96a71a4d",
+                "052aedffc554b41f52b521071793a6b88d6dbca9dba94cf34c83696de0c1ec35ca9c5ed4ab28059bd606a4f3a657eec0bb96661d42921b5f50a95ad33675b54f83",
+            ),
+            (
+                3,
+                "d3c7af07da2d54f7a7735d3d0fc4f0a73164db638b2f2f7c43f711f6d4aa7e64",
+                Some("c525714a7f49c28aedbbba78c005931a81c234b2f6c99a73e4d06082adc8bf2b"),
+                SIGHASH_ALL,
+                "bf013ea93474aa67815b1b6cc441d23b64fa310911d991e713cd34c7f5d46669",
+                "ff45f742a876139946a149ab4d9185574b98dc919d2eb6754f8abaa59d18b025637a3aa043b91817739554f4ed2026cf8022dbd83e351ce1fabc272841d2510a01",
+            ),
+            (
+                4,
+                "f36bb07a11e469ce941d16b63b11b9b9120a84d9d87cff2c84a8d4affb438f4e",
+                Some("ccbd66c6f7e8fdab47b3a486f59d28262be857f30d4773f2d5ea47f7761ce0e2"),
+                SIGHASH_DEFAULT,
+                "4f900a0bae3f1446fd48490c2958b5a023228f01661cda3496a11da502a7f7ef",
+                "b4010dd48a617db09926f729e79c33ae0b4e94b79f04a1ae93ede6315eb3669de185a17d2b0ac9ee09fd4c64b678a0b61a0a86fa888a273c8511be83bfd6810f",
+            ),
+            (
+                6,
+                "415cfe9c15d9cea27d8104d5517c06e9de48e2f986b695e4f5ffebf230e725d8",
+                Some("2f6b2c5397b6d68ca18e09a3f05161668ffe93a988582d55c6f07bd5b3329def"),
+                SIGHASH_NONE,
+                "15f25c298eb5cdc7eb1d638dd2d45c97c4c59dcaec6679cfc16ad84f30876b85",
+                "a3785919a2ce3c4ce26f298c3d51619bc474ae24014bcdd31328cd8cfbab2eff3395fa0a16fe5f486d12f22a9cedded5ae74feb4bbe5351346508c5405bcfee002",
+            ),
+            (
+                7,
+                "c7b0e81f0a9a0b0499e112279d718cca98e79a12e2f137c72ae5b213aad0d103",
+                Some("6c2dc106ab816b73f9d07e3cd1ef2c8c1256f519748e0813e4edd2405d277bef"),
+                SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+                "cd292de50313804dabe4685e83f923d2969577191a3e1d2882220dca88cbeb10",
+                "ea0c6ba90763c2d3a296ad82ba45881abb4f426b3f87af162dd24d5109edc1cdd11915095ba47c3a9963dc1e6c432939872bc49212fe34c632cd3ab9fed429c482",
+            ),
+            (
+                8,
+                "77863416be0d0665e517e1c375fd6f75839544eca553675ef7fdf4949518ebaa",
+                Some("ab179431c28d3b68fb798957faf5497d69c883c6fb1e1cd9f81483d87bac90cc"),
+                SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+                "cccb739eca6c13a8a89e6e5cd317ffe55669bbda23f2fd37b0f18755e008edd2",
+                "bbc9584a11074e83bc8c6759ec55401f0ae7b03ef290c3139814f545b58a9f8127258000874f44bc46db7646322107d4d86aec8e73b8719a61fff761d75b5dd981",
+            ),
+        ] {
+            assert_eq!(
+                hex::encode(cache.sig_hash(index, sighash_type, None, None).unwrap()),
+                expected_sig_hash
+            );
+
+            let internal_key = key(secret);
+            let merkle_root: Option<[u8; 32]> =
+                merkle_root.map(|root| hex::decode(root).unwrap().try_into().unwrap());
+            let output_key: [u8; 32] = prevouts[index].script_pubkey[2..].try_into().unwrap();
+            assert_eq!(
+                internal_key
+                    .taproot_tweak_key(merkle_root.as_ref())
+                    .unwrap()
+                    .xonly_public_key(),
+                output_key
+            );
+
+            signed
+                .sign_input_taproot_key_path(
+                    index,
+                    &prevouts,
+                    &internal_key,
+                    merkle_root.as_ref(),
+                    sighash_type,
+                    &[0u8; 32],
+                )
+                .unwrap();
+            let witness = &signed.tx_ins[index].witness;
+            assert_eq!(witness.len(), 1);
+            assert_eq!(hex::encode(&witness[0]), expected_witness);
+
+            // SIGHASH_DEFAULT の署名は64バイトで、それ以外は末尾に sighash type が付く
+            if sighash_type == SIGHASH_DEFAULT {
+                assert_eq!(witness[0].len(), 64);
+            } else {
+                assert_eq!(witness[0].len(), 65);
+                assert_eq!(u32::from(witness[0][64]), sighash_type);
+            }
+            let msg = tx.sig_hash_taproot(index, &prevouts, sighash_type).unwrap();
+            let sig: [u8; 64] = witness[0][..64].try_into().unwrap();
+            assert!(Secp256k1::schnorr_verify(&msg, &output_key, &sig));
+        }
+    }
+
+    #[test]
+    fn test_taproot_sig_hash_options() {
+        let tx = Tx {
+            version: 2,
+            tx_ins: (0..2).map(|i| TxIn::new([0xbb; 32], i)).collect(),
+            tx_outs: vec![TxOut {
+                amount: 1000,
+                script_pubkey: vec![0x51],
+            }],
+            locktime: 0,
+        };
+        let prevouts = vec![
+            TxOut {
+                amount: 2000,
+                script_pubkey: vec![0x51],
+            };
+            2
+        ];
+        let cache = TaprootCache::new(&tx, &prevouts).unwrap();
+
+        // annex と leaf hash はそれぞれ spend type を変える
+        let key_path = cache.sig_hash(0, SIGHASH_DEFAULT, None, None).unwrap();
+        let with_annex = cache
+            .sig_hash(0, SIGHASH_DEFAULT, Some(&[0x50]), None)
+            .unwrap();
+        let script_path = cache
+            .sig_hash(0, SIGHASH_DEFAULT, None, Some(&[0x01; 32]))
+            .unwrap();
+        assert_ne!(key_path, with_annex);
+        assert_ne!(key_path, script_path);
+        assert_ne!(
+            key_path,
+            cache.sig_hash(0, SIGHASH_ALL, None, None).unwrap()
+        );
+
+        assert_eq!(
+            cache.sig_hash(0, SIGHASH_DEFAULT, Some(&[0x51]), None),
+            Err(Error::InvalidTransaction("annex must start with 0x50"))
+        );
+        assert_eq!(
+            cache.sig_hash(0, 0x04, None, None),
+            Err(Error::InvalidTransaction("invalid taproot sighash type"))
+        );
+        assert_eq!(
+            cache.sig_hash(1, SIGHASH_SINGLE, None, None),
+            Err(Error::InvalidTransaction("no output for SIGHASH_SINGLE"))
+        );
+        assert_eq!(
+            TaprootCache::new(&tx, &prevouts[..1]),
+            Err(Error::InvalidTransaction(
+                "number of prevouts does not match inputs"
+            ))
+        );
+
+        // SIGHASH_DEFAULT の署名には sighash type を付けない
+        let internal_key = Secp256k1::from_private_key(Integer::from(8675309)).unwrap();
+        let mut signed = tx.clone();
+        signed
+            .sign_input_taproot_key_path(
+                0,
+                &prevouts,
+                &internal_key,
+                None,
+                SIGHASH_DEFAULT,
+                &[0u8; 32],
+            )
+            .unwrap();
+        let sig: [u8; 64] = signed.tx_ins[0].witness[0].clone().try_into().unwrap();
+        let output_key = internal_key.taproot_tweak_key(None).unwrap();
+        assert!(Secp256k1::schnorr_verify(
+            &key_path,
+            &output_key.xonly_public_key(),
+            &sig
+        ));
+    }
+}
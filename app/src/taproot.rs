@@ -0,0 +1,150 @@
+use rug::{integer::Order, Integer};
+use zeroize::Zeroizing;
+
+use crate::{
+    hash::create_tagged_hash,
+    keys::{tweak_add_scalar, PublicKey},
+    schnorr::has_even_y,
+    secp256k1::{to_scalar, Secp256k1},
+    tx::encode_varint,
+    Error,
+};
+
+/// BIP342 の tapscript のリーフバージョン
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+impl Secp256k1 {
+    /// 内部鍵を BIP341 の TapTweak で調整した出力鍵を求める
+    ///
+    /// t = hash_TapTweak(x(P) || merkle_root) として、y が偶数の P に対し Q = P + tG。
+    /// 秘密鍵があれば調整後の秘密鍵も求めるので、そのまま key path の署名に使える
+    pub fn taproot_tweak_key(&self, merkle_root: Option<&[u8; 32]>) -> Result<Self, Error> {
+        let internal_key = self.xonly_public_key();
+        let mut msg = internal_key.to_vec();
+        if let Some(merkle_root) = merkle_root {
+            msg.extend(merkle_root);
+        }
+        let tweak = Integer::from_digits(
+            create_tagged_hash("TapTweak", &msg).as_slice(),
+            Order::MsfBe,
+        );
+        let even = has_even_y(&self.public_key);
+
+        match self.checked_private_key() {
+            Ok(private_key) => {
+                // 秘密鍵は複製せず、消去されるスカラーの上で反転と調整を行う
+                let d = Zeroizing::new(to_scalar(private_key));
+                let d = if even { d } else { Zeroizing::new(-&*d) };
+                Ok(Secp256k1::from(tweak_add_scalar(&d, &tweak)?))
+            }
+            Err(Error::MissingPrivateKey) => {
+                let public_key = PublicKey::new(self.public_key.clone())?;
+                let public_key = if even {
+                    public_key
+                } else {
+                    public_key.negate()
+                };
+                Ok(Secp256k1::from(public_key.tweak_add(&tweak)?))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// スクリプトの木の葉のハッシュ hash_TapLeaf(leaf_version || compact_size(script) || script)
+pub fn tap_leaf_hash(leaf_version: u8, script: &[u8]) -> [u8; 32] {
+    let mut msg = vec![leaf_version];
+    msg.extend(encode_varint(script.len() as u64));
+    msg.extend(script);
+    create_tagged_hash("TapLeaf", &msg).try_into().unwrap()
+}
+
+/// 2つの子をバイト順に並べてから結合した枝のハッシュ
+pub fn tap_branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    create_tagged_hash("TapBranch", &[&left[..], &right[..]].concat())
+        .try_into()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Ecdsa;
+
+    use super::*;
+    use crate::{address::p2tr_address, Network};
+
+    fn internal_key(xonly: &str) -> Secp256k1 {
+        let x = Secp256k1::create_field_element(Integer::from_str_radix(xonly, 16).unwrap());
+        Secp256k1::new(None, Secp256k1::lift_x(x, false).unwrap())
+    }
+
+    #[test]
+    fn test_taproot_tweak_vectors() {
+        // BIP341 wallet test vectors (scriptPubKey 0, 1)
+        let output_key =
+            internal_key("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")
+                .taproot_tweak_key(None)
+                .unwrap();
+        assert_eq!(
+            hex::encode(output_key.xonly_public_key()),
+            "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+        assert_eq!(
+            p2tr_address(&output_key.xonly_public_key(), Network::Mainnet),
+            "bc1p2wsldez5mud2yam29q22wgfh9439spgduvct83k3pm50fcxa5dps59h4z5"
+        );
+
+        let leaf_hash = tap_leaf_hash(
+            TAPSCRIPT_LEAF_VERSION,
+            &hex::decode("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac")
+                .unwrap(),
+        );
+        assert_eq!(
+            hex::encode(leaf_hash),
+            "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"
+        );
+        let output_key =
+            internal_key("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27")
+                .taproot_tweak_key(Some(&leaf_hash))
+                .unwrap();
+        assert_eq!(
+            hex::encode(output_key.xonly_public_key()),
+            "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"
+        );
+        assert_eq!(
+            p2tr_address(&output_key.xonly_public_key(), Network::Mainnet),
+            "bc1pz37fc4cn9ah8anwm4xqqhvxygjf9rjf2resrw8h8w4tmvcs0863sa2e586"
+        );
+    }
+
+    #[test]
+    fn test_taproot_tweak_secret_key() {
+        let merkle_root = tap_branch_hash(&[0x01; 32], &[0x02; 32]);
+        assert_eq!(merkle_root, tap_branch_hash(&[0x02; 32], &[0x01; 32]));
+
+        // y が奇数の鍵も偶数の鍵も、秘密鍵と公開鍵で同じ出力鍵になる
+        let mut parities = vec![];
+        for secret in [1, 6] {
+            let key = Secp256k1::from_private_key(Integer::from(secret)).unwrap();
+            parities.push(has_even_y(&key.public_key));
+            let public_only = Secp256k1::new(None, key.public_key.clone());
+
+            for merkle_root in [None, Some(&merkle_root)] {
+                let tweaked = key.taproot_tweak_key(merkle_root).unwrap();
+                let tweaked_public = public_only.taproot_tweak_key(merkle_root).unwrap();
+                assert_eq!(tweaked.public_key, tweaked_public.public_key);
+                assert_eq!(tweaked_public.private_key, None);
+
+                let msg = [0x5a; 32];
+                let sig = tweaked.schnorr_sign(&msg, &[0u8; 32]).unwrap();
+                assert!(Secp256k1::schnorr_verify(
+                    &msg,
+                    &tweaked.xonly_public_key(),
+                    &sig
+                ));
+            }
+        }
+        assert!(parities.contains(&true) && parities.contains(&false));
+    }
+}
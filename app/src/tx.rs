@@ -0,0 +1,448 @@
+use crate::{hash::create_hash256, Error};
+
+/// BIP144 のSegWitトランザクションを示すマーカーとフラグ
+const SEGWIT_MARKER: u8 = 0x00;
+const SEGWIT_FLAG: u8 = 0x01;
+
+/// ビットコインのトランザクション
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tx {
+    pub version: u32,
+    pub tx_ins: Vec<TxIn>,
+    pub tx_outs: Vec<TxOut>,
+    pub locktime: u32,
+}
+
+/// トランザクションの入力
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    /// 使用する出力を含むトランザクションのID (表示と同じ順のバイト列)
+    pub prev_tx: [u8; 32],
+    pub prev_index: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    /// SegWitの witness。レガシーな入力では空
+    pub witness: Vec<Vec<u8>>,
+}
+
+/// トランザクションの出力
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    /// satoshi 単位の金額
+    pub amount: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+impl Tx {
+    /// レガシー形式、またはBIP144のSegWit形式のトランザクションを読み込む
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+        let version = reader.read_u32()?;
+
+        // 入力数の位置が0x00ならSegWitのマーカー
+        let segwit = reader.peek()? == SEGWIT_MARKER;
+        if segwit {
+            reader.read(1)?;
+            if reader.read_u8()? != SEGWIT_FLAG {
+                return Err(Error::InvalidTransaction("unknown segwit flag"));
+            }
+        }
+
+        let mut tx_ins = vec![];
+        for _ in 0..reader.read_varint()? {
+            tx_ins.push(TxIn::parse(&mut reader)?);
+        }
+        let mut tx_outs = vec![];
+        for _ in 0..reader.read_varint()? {
+            tx_outs.push(TxOut::parse(&mut reader)?);
+        }
+
+        if segwit {
+            for tx_in in tx_ins.iter_mut() {
+                for _ in 0..reader.read_varint()? {
+                    tx_in.witness.push(reader.read_var_bytes()?.to_vec());
+                }
+            }
+            if tx_ins.iter().all(|tx_in| tx_in.witness.is_empty()) {
+                return Err(Error::InvalidTransaction("superfluous witness record"));
+            }
+        }
+
+        let locktime = reader.read_u32()?;
+        if !reader.is_empty() {
+            return Err(Error::InvalidTransaction("trailing data"));
+        }
+
+        Ok(Tx {
+            version,
+            tx_ins,
+            tx_outs,
+            locktime,
+        })
+    }
+
+    /// witness を持つ入力があれば BIP144 の形式、なければレガシー形式で出力する
+    pub fn serialize(&self) -> Vec<u8> {
+        if !self.is_segwit() {
+            return self.serialize_legacy();
+        }
+
+        let mut result = self.version.to_le_bytes().to_vec();
+        result.extend([SEGWIT_MARKER, SEGWIT_FLAG]);
+        self.serialize_body(&mut result);
+        for tx_in in &self.tx_ins {
+            result.extend(encode_varint(tx_in.witness.len() as u64));
+            for item in &tx_in.witness {
+                result.extend(encode_varint(item.len() as u64));
+                result.extend(item);
+            }
+        }
+        result.extend(self.locktime.to_le_bytes());
+        result
+    }
+
+    /// witness を除いたレガシー形式で出力する (txid の計算に使う)
+    pub fn serialize_legacy(&self) -> Vec<u8> {
+        let mut result = self.version.to_le_bytes().to_vec();
+        self.serialize_body(&mut result);
+        result.extend(self.locktime.to_le_bytes());
+        result
+    }
+
+    pub fn is_segwit(&self) -> bool {
+        self.tx_ins.iter().any(|tx_in| !tx_in.witness.is_empty())
+    }
+
+    /// witness を除いたトランザクションのハッシュ (表示と同じ順)
+    pub fn txid(&self) -> [u8; 32] {
+        reversed_hash256(&self.serialize_legacy())
+    }
+
+    /// witness を含めたトランザクションのハッシュ (表示と同じ順)
+    pub fn wtxid(&self) -> [u8; 32] {
+        reversed_hash256(&self.serialize())
+    }
+
+    fn serialize_body(&self, result: &mut Vec<u8>) {
+        result.extend(encode_varint(self.tx_ins.len() as u64));
+        for tx_in in &self.tx_ins {
+            result.extend(tx_in.serialize());
+        }
+        result.extend(encode_varint(self.tx_outs.len() as u64));
+        for tx_out in &self.tx_outs {
+            result.extend(tx_out.serialize());
+        }
+    }
+}
+
+impl TxIn {
+    pub fn new(prev_tx: [u8; 32], prev_index: u32) -> Self {
+        TxIn {
+            prev_tx,
+            prev_index,
+            script_sig: vec![],
+            sequence: 0xffffffff,
+            witness: vec![],
+        }
+    }
+
+    /// witness を除いた入力のバイト列
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut result = self.prev_tx.iter().rev().copied().collect::<Vec<u8>>();
+        result.extend(self.prev_index.to_le_bytes());
+        result.extend(encode_varint(self.script_sig.len() as u64));
+        result.extend(&self.script_sig);
+        result.extend(self.sequence.to_le_bytes());
+        result
+    }
+
+    fn parse(reader: &mut Reader) -> Result<Self, Error> {
+        let mut prev_tx: [u8; 32] = reader.read(32)?.try_into().unwrap();
+        prev_tx.reverse();
+        Ok(TxIn {
+            prev_tx,
+            prev_index: reader.read_u32()?,
+            script_sig: reader.read_var_bytes()?.to_vec(),
+            sequence: reader.read_u32()?,
+            witness: vec![],
+        })
+    }
+}
+
+impl TxOut {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut result = self.amount.to_le_bytes().to_vec();
+        result.extend(encode_varint(self.script_pubkey.len() as u64));
+        result.extend(&self.script_pubkey);
+        result
+    }
+
+    fn parse(reader: &mut Reader) -> Result<Self, Error> {
+        Ok(TxOut {
+            amount: reader.read_u64()?,
+            script_pubkey: reader.read_var_bytes()?.to_vec(),
+        })
+    }
+}
+
+/// 可変長整数 (CompactSize) にエンコードする
+pub fn encode_varint(n: u64) -> Vec<u8> {
+    if n < 0xfd {
+        vec![n as u8]
+    } else if n <= 0xffff {
+        [&[0xfd], &(n as u16).to_le_bytes()[..]].concat()
+    } else if n <= 0xffffffff {
+        [&[0xfe], &(n as u32).to_le_bytes()[..]].concat()
+    } else {
+        [&[0xff], &n.to_le_bytes()[..]].concat()
+    }
+}
+
+/// 可変長整数を読み取り、値と読み取ったバイト数を返す
+pub fn decode_varint(bytes: &[u8]) -> Result<(u64, usize), Error> {
+    let mut reader = Reader::new(bytes);
+    let n = reader.read_varint()?;
+    Ok((n, reader.pos))
+}
+
+fn reversed_hash256(bytes: &[u8]) -> [u8; 32] {
+    let mut hash: [u8; 32] = create_hash256(bytes).try_into().unwrap();
+    hash.reverse();
+    hash
+}
+
+/// バイト列を先頭から順に読み取る
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn peek(&self) -> Result<u8, Error> {
+        self.bytes
+            .get(self.pos)
+            .copied()
+            .ok_or(Error::UnexpectedEndOfData)
+    }
+
+    fn read(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(Error::UnexpectedEndOfData)?;
+        let result = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(result)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.read(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.read(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.read(8)?.try_into().unwrap()))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, Error> {
+        // 最短の表現でなければ不正とする
+        let (n, min) = match self.read_u8()? {
+            0xfd => (u64::from(self.read_u16()?), 0xfd),
+            0xfe => (u64::from(self.read_u32()?), 0x10000),
+            0xff => (self.read_u64()?, 0x100000000),
+            n => return Ok(u64::from(n)),
+        };
+        if n < min {
+            return Err(Error::InvalidTransaction("non-canonical varint"));
+        }
+        Ok(n)
+    }
+
+    fn read_var_bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.read_varint()?;
+        let len = usize::try_from(len).map_err(|_| Error::UnexpectedEndOfData)?;
+        self.read(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Programming Bitcoin 5章のメインネットのトランザクション
+    const LEGACY_TX: &str = "0100000001813f79011acb80925dfe69b3def355fe914bd1d96a3f5f71bf8303c6a989c7d1000000006b483045022100ed81ff192e75a3fd2304004dcadb746fa5e24c5031ccfcf21320b0277457c98f02207a986d955c6e0cb35d446a89d3f56100f4d7f67801c31967743a9c8e10615bed01210349fc4e631e3624a545de3f89f5d8684c7b8138bd94bdd531d2e213bf016b278afeffffff02a135ef01000000001976a914bc3b654dca7e56b04dca18f2566cdaf02e8d9ada88ac99c39800000000001976a9141c4bc762dd5423e332166702cb75f40df79fea1288ac19430600";
+
+    // BIP143 の Native P2WPKH の署名済みトランザクション
+    const SEGWIT_TX: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+    /// メインネットの P2WPKH を使用するトランザクション
+    /// (txid f5864806e3565c34d1b41e716f72609d00b55ea5eac5b924c9719a842ef42206)
+    const MAINNET_SEGWIT_TX: &str = "02000000000101595895ea20179de87052b4046dfe6fd515860505d6511a9004cf12a1f93cac7c0100000000ffffffff01deb807000000000017a9140f3444e271620c736808aa7b33e370bd87cb5a078702483045022100fb60dad8df4af2841adc0346638c16d0b8035f5e3f3753b88db122e70c79f9370220756e6633b17fd2710e626347d28d60b0a2d6cbb41de51740644b9fb3ba7751040121028fa937ca8cba2197a37c007176ed8941055d3bcb8627d085e94553e62f057dcc00000000";
+
+    #[test]
+    fn test_varint() {
+        for (n, encoded) in [
+            (0, "00"),
+            (0xfc, "fc"),
+            (0xfd, "fdfd00"),
+            (0xffff, "fdffff"),
+            (0x10000, "fe00000100"),
+            (0xffffffff, "feffffffff"),
+            (0x100000000, "ff0000000001000000"),
+        ] {
+            assert_eq!(hex::encode(encode_varint(n)), encoded);
+            assert_eq!(
+                decode_varint(&hex::decode(encoded).unwrap()),
+                Ok((n, encoded.len() / 2))
+            );
+        }
+        assert_eq!(
+            decode_varint(&hex::decode("fd0100").unwrap()),
+            Err(Error::InvalidTransaction("non-canonical varint"))
+        );
+        assert_eq!(
+            decode_varint(&[0xfe, 0x00]),
+            Err(Error::UnexpectedEndOfData)
+        );
+    }
+
+    #[test]
+    fn test_parse_legacy() {
+        let bytes = hex::decode(LEGACY_TX).unwrap();
+        let tx = Tx::parse(&bytes).unwrap();
+
+        assert_eq!(tx.version, 1);
+        assert_eq!(tx.tx_ins.len(), 1);
+        assert_eq!(
+            hex::encode(tx.tx_ins[0].prev_tx),
+            "d1c789a9c60383bf715f3f6ad9d14b91fe55f3deb369fe5d9280cb1a01793f81"
+        );
+        assert_eq!(tx.tx_ins[0].prev_index, 0);
+        assert_eq!(tx.tx_ins[0].script_sig.len(), 0x6b);
+        assert_eq!(tx.tx_ins[0].sequence, 0xfffffffe);
+        assert_eq!(tx.tx_outs.len(), 2);
+        assert_eq!(tx.tx_outs[0].amount, 32454049);
+        assert_eq!(
+            hex::encode(&tx.tx_outs[1].script_pubkey),
+            "76a9141c4bc762dd5423e332166702cb75f40df79fea1288ac"
+        );
+        assert_eq!(tx.tx_outs[1].amount, 10011545);
+        assert_eq!(tx.locktime, 410393);
+
+        assert!(!tx.is_segwit());
+        assert_eq!(tx.serialize(), bytes);
+        assert_eq!(
+            hex::encode(tx.txid()),
+            "452c629d67e41baec3ac6f04fe744b4b9617f8f859c63b3002f8684e7a4fee03"
+        );
+        assert_eq!(tx.wtxid(), tx.txid());
+    }
+
+    #[test]
+    fn test_parse_segwit() {
+        let bytes = hex::decode(SEGWIT_TX).unwrap();
+        let tx = Tx::parse(&bytes).unwrap();
+
+        assert!(tx.is_segwit());
+        assert_eq!(tx.tx_ins.len(), 2);
+        assert_eq!(tx.tx_ins[0].witness, Vec::<Vec<u8>>::new());
+        assert_eq!(tx.tx_ins[1].witness.len(), 2);
+        assert_eq!(
+            hex::encode(&tx.tx_ins[1].witness[1]),
+            "025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357"
+        );
+        assert_eq!(tx.locktime, 17);
+        assert_eq!(tx.serialize(), bytes);
+
+        // txid は witness を除いた形式から計算する
+        let legacy = tx.serialize_legacy();
+        assert_eq!(&legacy[..4], &bytes[..4]);
+        assert_eq!(legacy[4], 0x02);
+        let mut without_witness = tx.clone();
+        without_witness
+            .tx_ins
+            .iter_mut()
+            .for_each(|tx_in| tx_in.witness.clear());
+        assert_eq!(without_witness.serialize(), legacy);
+        assert_eq!(Tx::parse(&legacy).unwrap(), without_witness);
+        assert_eq!(without_witness.txid(), tx.txid());
+        assert_eq!(without_witness.wtxid(), tx.txid());
+        assert_ne!(tx.wtxid(), tx.txid());
+    }
+
+    #[test]
+    fn test_parse_mainnet_segwit() {
+        let bytes = hex::decode(MAINNET_SEGWIT_TX).unwrap();
+        let tx = Tx::parse(&bytes).unwrap();
+
+        assert!(tx.is_segwit());
+        assert_eq!(tx.version, 2);
+        assert_eq!(tx.tx_ins.len(), 1);
+        assert_eq!(
+            hex::encode(tx.tx_ins[0].prev_tx),
+            "7cac3cf9a112cf04901a51d605058615d56ffe6d04b45270e89d1720ea955859"
+        );
+        assert_eq!(tx.tx_ins[0].prev_index, 1);
+        assert!(tx.tx_ins[0].script_sig.is_empty());
+        assert_eq!(tx.tx_ins[0].witness.len(), 2);
+        assert_eq!(tx.tx_outs.len(), 1);
+        assert_eq!(tx.tx_outs[0].amount, 506078);
+        assert_eq!(tx.locktime, 0);
+        assert_eq!(tx.serialize(), bytes);
+
+        assert_eq!(
+            hex::encode(tx.txid()),
+            "f5864806e3565c34d1b41e716f72609d00b55ea5eac5b924c9719a842ef42206"
+        );
+        assert_eq!(
+            hex::encode(tx.wtxid()),
+            "80b7d8a82d5d5bf92905b06f2014dd699e03837ca172e3a59d51426ebbe3e7f5"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let bytes = hex::decode(LEGACY_TX).unwrap();
+        assert_eq!(
+            Tx::parse(&bytes[..bytes.len() - 1]),
+            Err(Error::UnexpectedEndOfData)
+        );
+        assert_eq!(
+            Tx::parse(&[&bytes[..], &[0x00]].concat()),
+            Err(Error::InvalidTransaction("trailing data"))
+        );
+
+        // witness がすべて空のSegWit形式は認めない
+        let tx = Tx::parse(&bytes).unwrap();
+        let mut segwit = tx.version.to_le_bytes().to_vec();
+        segwit.extend([SEGWIT_MARKER, SEGWIT_FLAG]);
+        segwit.extend(&bytes[4..bytes.len() - 4]);
+        segwit.push(0x00);
+        segwit.extend(tx.locktime.to_le_bytes());
+        assert_eq!(
+            Tx::parse(&segwit),
+            Err(Error::InvalidTransaction("superfluous witness record"))
+        );
+        segwit[5] = 0x02;
+        assert_eq!(
+            Tx::parse(&segwit),
+            Err(Error::InvalidTransaction("unknown segwit flag"))
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use zeroize::Zeroizing;
+
+use crate::{
+    base58::{decode_base58_checksum, encode_base58_checksum},
+    keys::SecretKey,
+    secp256k1::{to_32_bytes, Secp256k1},
+    Error, Network,
+};
+
+/// 圧縮公開鍵を使う秘密鍵の末尾に付けるフラグ
+const COMPRESSED_FLAG: u8 = 0x01;
+
+/// WIFから読み込んだ鍵と、その鍵をどのアドレス形式で使うか
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifKey {
+    pub key: Secp256k1,
+    pub network: Network,
+    pub compressed: bool,
+}
+
+impl Secp256k1 {
+    /// 秘密鍵を Wallet Import Format で出力する
+    pub fn to_wif(&self, compressed: bool, network: Network) -> Result<String, Error> {
+        let private_key = self.checked_private_key()?;
+
+        // 秘密鍵を含むバッファは drop 時に消去する
+        let mut payload = Zeroizing::new(Vec::with_capacity(34));
+        payload.push(network.wif_prefix());
+        payload.extend(Zeroizing::new(to_32_bytes(private_key)).iter());
+        if compressed {
+            payload.push(COMPRESSED_FLAG);
+        }
+        Ok(encode_base58_checksum(&payload))
+    }
+
+    /// WIF形式の秘密鍵を読み込み、公開鍵も求める
+    pub fn from_wif(wif: &str) -> Result<WifKey, Error> {
+        let payload = Zeroizing::new(decode_base58_checksum(wif)?);
+        let compressed = match payload.len() {
+            33 => false,
+            34 if payload[33] == COMPRESSED_FLAG => true,
+            34 => return Err(Error::InvalidWifCompressionFlag(payload[33])),
+            length => return Err(Error::InvalidWifLength(length)),
+        };
+        let network =
+            Network::from_wif_prefix(payload[0]).ok_or(Error::InvalidWifPrefix(payload[0]))?;
+
+        // 整数への変換と範囲外の値の消去は SecretKey に任せる
+        let secret_key = SecretKey::from_bytes(payload[1..33].try_into().unwrap())?;
+        Ok(WifKey {
+            key: Secp256k1::from(secret_key),
+            network,
+            compressed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Ecdsa;
+    use rug::{ops::Pow, Integer};
+
+    use super::*;
+
+    #[test]
+    fn test_wif() {
+        let cases = [
+            (
+                Integer::from(5003),
+                true,
+                Network::Testnet,
+                "cMahea7zqjxrtgAbB7LSGbcQUr1uX1ojuat9jZodMN8rFTv2sfUK",
+            ),
+            (
+                Integer::from(2021).pow(5),
+                false,
+                Network::Testnet,
+                "91avARGdfge8E4tZfYLoxeJ5sGBdNJQH4kvjpWAxgzczjbCwxic",
+            ),
+            (
+                Integer::from(0x54321deadbeefu64),
+                true,
+                Network::Mainnet,
+                "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgiuQJv1h8Ytr2S53a",
+            ),
+            (
+                Integer::from_str_radix(
+                    "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d",
+                    16,
+                )
+                .unwrap(),
+                false,
+                Network::Mainnet,
+                "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+            ),
+        ];
+        for (secret, compressed, network, wif) in cases {
+            let sec256 = Secp256k1::from_private_key(secret).unwrap();
+            assert_eq!(sec256.to_wif(compressed, network).unwrap(), wif);
+
+            let decoded = Secp256k1::from_wif(wif).unwrap();
+            assert_eq!(decoded.key, sec256);
+            assert_eq!(decoded.network, network);
+            assert_eq!(decoded.compressed, compressed);
+        }
+
+        let public_key = Secp256k1::get_g();
+        assert_eq!(
+            Secp256k1::new(None, public_key).to_wif(true, Network::Mainnet),
+            Err(Error::MissingPrivateKey)
+        );
+        assert_eq!(
+            Secp256k1::new(Some(Secp256k1::get_n()), Secp256k1::get_g())
+                .to_wif(true, Network::Mainnet),
+            Err(Error::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_from_wif_invalid() {
+        let wif = |payload: &[u8]| encode_base58_checksum(payload);
+        let key = [0x11u8; 32];
+
+        assert_eq!(
+            Secp256k1::from_wif(&wif(&[&[0x80], &key[..], &[0x02]].concat())),
+            Err(Error::InvalidWifCompressionFlag(0x02))
+        );
+        assert_eq!(
+            Secp256k1::from_wif(&wif(&[&[0x80], &key[..31]].concat())),
+            Err(Error::InvalidWifLength(32))
+        );
+        assert_eq!(
+            Secp256k1::from_wif(&wif(&[&[0x00], &key[..]].concat())),
+            Err(Error::InvalidWifPrefix(0x00))
+        );
+        assert_eq!(
+            Secp256k1::from_wif(&wif(&[&[0x80], &[0u8; 32][..]].concat())),
+            Err(Error::InvalidPrivateKey)
+        );
+        assert_eq!(
+            Secp256k1::from_wif(&wif(&[&[0x80], &[0xffu8; 32][..]].concat())),
+            Err(Error::InvalidPrivateKey)
+        );
+        assert_eq!(
+            Secp256k1::from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgiuQJv1h8Ytr2S53b"),
+            Err(Error::InvalidBase58Checksum)
+        );
+    }
+}
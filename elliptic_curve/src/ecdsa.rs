@@ -1,14 +1,16 @@
 use crate::{signature::Signature, Point};
 
-pub trait Ecdsa<T, U> {
+pub trait Ecdsa<T, U>: Sized {
+    type Error;
+
     fn new(private_key: Option<U>, public_key: Point<T, U>) -> Self;
     fn generate_key_pair_from_secret(secret: &str) -> Self;
-    fn generate_public_key_from_coord(x: U, y: U) -> Point<T, U>;
+    fn generate_public_key_from_coord(x: U, y: U) -> Result<Point<T, U>, Self::Error>;
     fn verify(&self, z: U, sig: Signature<U>) -> bool;
-    fn sign(&self, z: U, k: U) -> Signature<U>;
+    fn sign(&self, z: U, k: U) -> Result<Signature<U>, Self::Error>;
     fn get_n() -> U;
     fn get_g() -> Point<T, U>;
-    fn deterministic_k(&self, z: U) -> U;
+    fn deterministic_k(&self, z: U) -> Result<U, Self::Error>;
     fn sec(&self, compress: bool) -> Vec<u8>;
-    fn parse_sec(sec: &[u8]) -> Self;
+    fn parse_sec(sec: &[u8]) -> Result<Self, Self::Error>;
 }
@@ -0,0 +1,44 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// 点 `(x, y)` が曲線上にない
+    NotOnCurve { x: String, y: String },
+    /// 異なる曲線上の点同士の演算
+    DifferentCurves,
+    /// DER形式の署名が不正
+    InvalidDer(&'static str),
+    /// マルチスカラー倍算に点が1つも渡されていない
+    EmptyTerms,
+    /// 有限体の演算で発生したエラー
+    FieldElement(field_element::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotOnCurve { x, y } => write!(f, "({}, {}) is not on the curve", x, y),
+            Error::DifferentCurves => write!(f, "Points are not on the same curve"),
+            Error::InvalidDer(reason) => write!(f, "Invalid DER signature: {}", reason),
+            Error::EmptyTerms => {
+                write!(f, "Multi-scalar multiplication requires at least one term")
+            }
+            Error::FieldElement(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::FieldElement(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<field_element::Error> for Error {
+    fn from(e: field_element::Error) -> Self {
+        Error::FieldElement(e)
+    }
+}
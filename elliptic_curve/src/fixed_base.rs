@@ -0,0 +1,152 @@
+use std::{
+    fmt::Debug,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use field_element::Field;
+use num_traits::Pow;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+use crate::{Point, ProjectivePoint};
+
+/// 1つの窓のbit数
+const WINDOW_BITS: usize = 4;
+/// 256bitのスカラーを分割した窓の数
+const WINDOWS: usize = 256 / WINDOW_BITS;
+/// 1つの窓に入る値の数
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+
+/// 固定の基点に対するスカラー倍算用の事前計算テーブル
+///
+/// i番目の窓に j * 16^i * base (j = 0..16) を保持しておくと、
+/// スカラーを4bitずつに分けて表を引き、64回の加算だけで base の倍数が求まる
+#[derive(Debug)]
+pub struct FixedBaseTable<T, U> {
+    windows: Vec<Vec<ProjectivePoint<T, U>>>,
+}
+
+impl<T, U> FixedBaseTable<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field
+        + ConditionallySelectable,
+{
+    /// 完全加算公式を使うので、a = 0 でない曲線の点を渡すとパニックする
+    pub fn new(base: Point<T, U>) -> Self {
+        assert!(base.a.is_zero(), "complete formulas require a = 0");
+        let mut windows = Vec::with_capacity(WINDOWS);
+        let mut window_base = ProjectivePoint::from(base);
+        for _ in 0..WINDOWS {
+            let mut window = Vec::with_capacity(WINDOW_SIZE);
+            let mut current = ProjectivePoint::infinity(window_base.a, window_base.b);
+            for _ in 0..WINDOW_SIZE {
+                window.push(current.clone());
+                current = current.add(&window_base);
+            }
+            // current = 16 * window_base
+            window_base = current;
+            windows.push(window);
+        }
+        Self { windows }
+    }
+
+    /// スカラー (32バイトのビッグエンディアン) 倍した点を求める
+    ///
+    /// 表引きは窓内の全要素を走査して条件付き選択するので、
+    /// 処理時間やメモリアクセスのパターンはスカラーに依存しない
+    pub fn mul_ct(&self, scalar: &[u8; 32]) -> ProjectivePoint<T, U> {
+        let first = &self.windows[0][0];
+        let mut result = ProjectivePoint::infinity(first.a, first.b);
+        for (i, window) in self.windows.iter().enumerate() {
+            let byte = scalar[31 - i / 2];
+            let digit = if i % 2 == 0 { byte & 0x0f } else { byte >> 4 };
+
+            let mut entry = window[0].clone();
+            for (j, candidate) in window.iter().enumerate().skip(1) {
+                let choice = (j as u8).ct_eq(&digit);
+                entry = ProjectivePoint::conditional_select(&entry, candidate, choice);
+            }
+            result = result.add(&entry);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use field_element::Fe256;
+    use rug::{integer::Order, rand::RandState, Integer};
+
+    use super::FixedBaseTable;
+    use crate::{Point, ProjectivePoint};
+
+    fn generator() -> Point<Fe256, Integer> {
+        let gx = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let gy = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+        Point::new(
+            Some(Fe256::from_bytes(&hex::decode(gx).unwrap().try_into().unwrap()).unwrap()),
+            Some(Fe256::from_bytes(&hex::decode(gy).unwrap().try_into().unwrap()).unwrap()),
+            Fe256::ZERO,
+            Fe256::from_u64(7),
+        )
+    }
+
+    fn to_bytes(scalar: &Integer) -> [u8; 32] {
+        let digits = scalar.to_digits::<u8>(Order::MsfBe);
+        let mut bytes = [0u8; 32];
+        bytes[32 - digits.len()..].copy_from_slice(&digits);
+        bytes
+    }
+
+    #[test]
+    fn test_fixed_base_mul_ct() {
+        let g = generator();
+        let table = FixedBaseTable::new(g.clone());
+        let pg = ProjectivePoint::from(g.clone());
+        let n = Integer::from_str_radix(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            16,
+        )
+        .unwrap();
+
+        let mut rand = RandState::new();
+        let mut scalars = vec![
+            Integer::from(0),
+            Integer::from(1),
+            Integer::from(15),
+            Integer::from(16),
+            Integer::from(0xfedc),
+            n.clone() - 1,
+        ];
+        for _ in 0..8 {
+            scalars.push(Integer::from(n.random_below_ref(&mut rand)));
+        }
+        for k in scalars {
+            let bytes = to_bytes(&k);
+            assert_eq!(
+                table.mul_ct(&bytes).to_affine(),
+                pg.mul_ct(&bytes).to_affine()
+            );
+        }
+        assert!(table.mul_ct(&to_bytes(&n)).is_infinity());
+    }
+
+    #[test]
+    #[should_panic(expected = "complete formulas require a = 0")]
+    fn test_fixed_base_requires_a_zero() {
+        FixedBaseTable::new(Point::<Fe256, Integer>::new(
+            None,
+            None,
+            Fe256::from_u64(1),
+            Fe256::from_u64(7),
+        ));
+    }
+}
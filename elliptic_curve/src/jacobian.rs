@@ -0,0 +1,414 @@
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Add, BitAnd, Div, Mul, ShrAssign, Sub},
+};
+
+use field_element::Field;
+use num_traits::Pow;
+
+use crate::{Error, Point};
+
+/// ヤコビアン座標 (X, Y, Z) で表した楕円曲線上の点
+///
+/// アフィン座標 (X / Z^2, Y / Z^3) に対応し、Z = 0 が無限遠点を表す。
+/// 加算・二倍算で除算を行わないため、スカラー倍算の最後に1回だけ逆元を求めればよい
+#[derive(Debug)]
+pub struct JacobianPoint<T, U> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub a: T,
+    pub b: T,
+    _maker: PhantomData<fn() -> U>,
+}
+
+impl<T, U> JacobianPoint<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+{
+    pub fn infinity(a: T, b: T) -> Self {
+        Self {
+            x: a.one(),
+            y: a.one(),
+            z: a.zero(),
+            a,
+            b,
+            _maker: PhantomData,
+        }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// アフィン座標に変換する (逆元を1回求める)
+    pub fn to_affine(&self) -> Point<T, U> {
+        if self.is_infinity() {
+            return Point::new(None, None, self.a.clone(), self.b.clone());
+        }
+        let z_inv = self.z.one() / self.z.clone();
+        let z_inv2 = z_inv.clone() * z_inv.clone();
+        let x = self.x.clone() * z_inv2.clone();
+        let y = self.y.clone() * z_inv2 * z_inv;
+        Point::new(Some(x), Some(y), self.a.clone(), self.b.clone())
+    }
+
+    pub fn double(&self) -> Self {
+        if self.is_infinity() || self.y.is_zero() {
+            return Self::infinity(self.a.clone(), self.b.clone());
+        }
+
+        // dbl-2007-bl
+        let xx = self.x.clone().pow(2);
+        let yy = self.y.clone().pow(2);
+        let yyyy = yy.clone().pow(2);
+        let zz = self.z.clone().pow(2);
+        let s = self.x.clone() * yy * 4;
+        let m = xx * 3 + self.a.clone() * zz.pow(2);
+        let x3 = m.clone().pow(2) - s.clone() * 2;
+        let y3 = m * (s - x3.clone()) - yyyy * 8;
+        let z3 = self.y.clone() * self.z.clone() * 2;
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+            a: self.a.clone(),
+            b: self.b.clone(),
+            _maker: PhantomData,
+        }
+    }
+
+    /// アフィン座標の点との加算 (Z2 = 1 として計算を省略する)
+    pub fn add_affine(&self, other: &Point<T, U>) -> Self {
+        let (x2, y2) = match (&other.x, &other.y) {
+            (Some(x2), Some(y2)) => (x2.clone(), y2.clone()),
+            _ => return self.clone(),
+        };
+        if self.is_infinity() {
+            return Self {
+                z: x2.one(),
+                x: x2,
+                y: y2,
+                a: self.a.clone(),
+                b: self.b.clone(),
+                _maker: PhantomData,
+            };
+        }
+
+        // madd-2007-bl
+        let z1z1 = self.z.clone().pow(2);
+        let u2 = x2 * z1z1.clone();
+        let s2 = y2 * self.z.clone() * z1z1;
+        if self.x == u2 {
+            if self.y == s2 {
+                return self.double();
+            }
+            return Self::infinity(self.a.clone(), self.b.clone());
+        }
+
+        let h = u2 - self.x.clone();
+        let r = s2 - self.y.clone();
+        let hh = h.clone().pow(2);
+        let hhh = h.clone() * hh.clone();
+        let v = self.x.clone() * hh;
+        let x3 = r.clone().pow(2) - hhh.clone() - v.clone() * 2;
+        let y3 = r * (v - x3.clone()) - self.y.clone() * hhh;
+        let z3 = self.z.clone() * h;
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+            a: self.a.clone(),
+            b: self.b.clone(),
+            _maker: PhantomData,
+        }
+    }
+}
+
+// derive(Clone) だと U: Clone が要求されるため手動で実装する
+impl<T: Clone, U> Clone for JacobianPoint<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            x: self.x.clone(),
+            y: self.y.clone(),
+            z: self.z.clone(),
+            a: self.a.clone(),
+            b: self.b.clone(),
+            _maker: PhantomData,
+        }
+    }
+}
+
+impl<T, U> From<Point<T, U>> for JacobianPoint<T, U>
+where
+    T: Field,
+{
+    fn from(point: Point<T, U>) -> Self {
+        match (point.x, point.y) {
+            (Some(x), Some(y)) => Self {
+                z: x.one(),
+                x,
+                y,
+                a: point.a,
+                b: point.b,
+                _maker: PhantomData,
+            },
+            _ => Self {
+                x: point.a.one(),
+                y: point.a.one(),
+                z: point.a.zero(),
+                a: point.a,
+                b: point.b,
+                _maker: PhantomData,
+            },
+        }
+    }
+}
+
+impl<T, U> PartialEq for JacobianPoint<T, U>
+where
+    T: Mul<Output = T> + PartialEq + Clone + Field,
+{
+    /// 座標の表現ではなく、対応するアフィン座標の点が等しいかを比較する
+    fn eq(&self, other: &Self) -> bool {
+        if self.a != other.a || self.b != other.b {
+            return false;
+        }
+        match (self.z.is_zero(), other.z.is_zero()) {
+            (true, true) => return true,
+            (true, false) | (false, true) => return false,
+            _ => {}
+        }
+        let z1z1 = self.z.clone() * self.z.clone();
+        let z2z2 = other.z.clone() * other.z.clone();
+        self.x.clone() * z2z2.clone() == other.x.clone() * z1z1.clone()
+            && self.y.clone() * z2z2 * other.z.clone() == other.y.clone() * z1z1 * self.z.clone()
+    }
+}
+
+impl<T, U> Add for JacobianPoint<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        if self.a != other.a || self.b != other.b {
+            panic!("{}", Error::DifferentCurves);
+        }
+        if self.is_infinity() {
+            return other;
+        }
+        if other.is_infinity() {
+            return self;
+        }
+
+        // add-2007-bl
+        let z1z1 = self.z.clone().pow(2);
+        let z2z2 = other.z.clone().pow(2);
+        let u1 = self.x.clone() * z2z2.clone();
+        let u2 = other.x.clone() * z1z1.clone();
+        let s1 = self.y.clone() * other.z.clone() * z2z2;
+        let s2 = other.y.clone() * self.z.clone() * z1z1;
+        if u1 == u2 {
+            if s1 == s2 {
+                return self.double();
+            }
+            return Self::infinity(self.a, self.b);
+        }
+
+        let h = u2 - u1.clone();
+        let r = s2 - s1.clone();
+        let hh = h.clone().pow(2);
+        let hhh = h.clone() * hh.clone();
+        let v = u1 * hh;
+        let x3 = r.clone().pow(2) - hhh.clone() - v.clone() * 2;
+        let y3 = r * (v - x3.clone()) - s1 * hhh;
+        let z3 = self.z * other.z * h;
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+            a: self.a,
+            b: self.b,
+            _maker: PhantomData,
+        }
+    }
+}
+
+impl<T, U> Add<Point<T, U>> for JacobianPoint<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+{
+    type Output = Self;
+
+    fn add(self, other: Point<T, U>) -> Self {
+        if self.a != other.a || self.b != other.b {
+            panic!("{}", Error::DifferentCurves);
+        }
+        self.add_affine(&other)
+    }
+}
+
+impl<T, U> Mul<U> for JacobianPoint<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+    U: Clone + PartialOrd + From<i32> + BitAnd<Output = U> + ShrAssign<i32>,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: U) -> Self::Output {
+        let mut coef = rhs;
+        let mut current = self.clone();
+        let mut result = Self::infinity(self.a, self.b);
+        while coef > 0.into() {
+            if coef.clone() & 1.into() == 1.into() {
+                result = result + current.clone();
+            }
+            current = current.double();
+            coef >>= 1;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::Integer;
+
+    use super::JacobianPoint;
+    use crate::Point;
+    use field_element::FieldElement;
+
+    fn point(x: i32, y: i32) -> Point<FieldElement<Integer>, Integer> {
+        Point::new(
+            Some(FieldElement::new(Integer::from(x), Integer::from(223))),
+            Some(FieldElement::new(Integer::from(y), Integer::from(223))),
+            FieldElement::new(Integer::from(0), Integer::from(223)),
+            FieldElement::new(Integer::from(7), Integer::from(223)),
+        )
+    }
+
+    fn infinity() -> Point<FieldElement<Integer>, Integer> {
+        Point::new(
+            None,
+            None,
+            FieldElement::new(Integer::from(0), Integer::from(223)),
+            FieldElement::new(Integer::from(7), Integer::from(223)),
+        )
+    }
+
+    #[test]
+    fn test_jacobian_conversion() {
+        let p1 = point(47, 71);
+        let j1 = JacobianPoint::from(p1.clone());
+
+        assert_eq!(j1.to_affine(), p1);
+        assert_eq!(JacobianPoint::from(infinity()).to_affine(), infinity());
+        assert!(JacobianPoint::from(infinity()).is_infinity());
+
+        // Zが1でない表現も同じ点として扱う
+        let j2 = j1.double() + j1.clone();
+        let j3 = j1.clone() + j1.double();
+        assert_eq!(j2, j3);
+        assert_ne!(j2, j1);
+    }
+
+    #[test]
+    fn test_jacobian_add() {
+        let p1 = point(170, 142);
+        let p2 = point(60, 139);
+        let p3 = point(47, 71);
+        let p4 = point(143, 98);
+        let j1 = JacobianPoint::from(p1.clone());
+        let j2 = JacobianPoint::from(p2.clone());
+
+        assert_eq!(
+            (j1.clone() + j2.clone()).to_affine(),
+            p1.clone() + p2.clone()
+        );
+        assert_eq!((j1.clone() + p2.clone()).to_affine(), p1.clone() + p2);
+        assert_eq!(j1.double().to_affine(), p1.clone() + p1.clone());
+        assert_eq!(
+            (j1.clone() + j1.clone()).to_affine(),
+            p1.clone() + p1.clone()
+        );
+        assert_eq!(
+            (j1.clone() + p1.clone()).to_affine(),
+            p1.clone() + p1.clone()
+        );
+
+        // 二倍した点同士 (Zが1でない) の加算
+        let j3 = JacobianPoint::from(p3.clone()).double();
+        let j4 = JacobianPoint::from(p4.clone()).double();
+        assert_eq!((j3 + j4).to_affine(), (p3.clone() + p3) + (p4.clone() + p4));
+
+        // 加法単位元・逆元との加算
+        let neg_p1 = point(170, 223 - 142);
+        assert_eq!(
+            (j1.clone() + JacobianPoint::from(infinity())).to_affine(),
+            p1
+        );
+        assert_eq!(
+            (JacobianPoint::from(infinity()) + j1.clone()).to_affine(),
+            p1
+        );
+        assert!((j1.clone() + JacobianPoint::from(neg_p1.clone())).is_infinity());
+        assert!((j1 + neg_p1).is_infinity());
+    }
+
+    #[test]
+    fn test_jacobian_mul() {
+        // (47, 71) の位数は21
+        let p = point(47, 71);
+        let mut expected = infinity();
+        for k in 0..=21 {
+            assert_eq!(
+                (JacobianPoint::from(p.clone()) * Integer::from(k)).to_affine(),
+                expected
+            );
+            assert_eq!(p.clone() * Integer::from(k), expected);
+            expected = expected + p.clone();
+        }
+    }
+}
@@ -2,5 +2,16 @@ mod point;
 pub use point::Point;
 pub use signature::Signature;
 mod ecdsa;
+mod error;
+mod fixed_base;
+mod jacobian;
+mod msm;
+mod projective;
 mod signature;
+mod wnaf;
 pub use ecdsa::Ecdsa;
+pub use error::Error;
+pub use fixed_base::FixedBaseTable;
+pub use jacobian::JacobianPoint;
+pub use projective::ProjectivePoint;
+pub use wnaf::{multi_mul_wnaf, wnaf};
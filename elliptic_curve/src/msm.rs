@@ -0,0 +1,180 @@
+use std::{
+    fmt::Debug,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use field_element::Field;
+use num_traits::Pow;
+use rug::Integer;
+
+use crate::{multi_mul_wnaf, Error, JacobianPoint, Point};
+
+/// この数以上の点ではPippengerのバケット法に切り替える
+const PIPPENGER_THRESHOLD: usize = 32;
+
+/// Strauss法で使うwNAFの幅
+const STRAUSS_WNAF_WIDTH: u32 = 5;
+
+impl<T> Point<T, Integer>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+{
+    /// Σ k_i P_i をまとめて計算する (マルチスカラー倍算)
+    ///
+    /// 点が少ないときはwNAFで二倍算を共有するStrauss法、多いときはPippengerのバケット法を使う。
+    /// 実行時間がスカラーに依存するので、公開データにのみ使うこと。
+    /// 点が1つもなければ曲線 (a, b) が決まらないので `Error::EmptyTerms` を返す
+    pub fn multi_scalar_mul(terms: &[(Self, Integer)]) -> Result<Self, Error> {
+        if terms.is_empty() {
+            return Err(Error::EmptyTerms);
+        }
+        if terms.len() < PIPPENGER_THRESHOLD {
+            Ok(multi_mul_wnaf(terms, STRAUSS_WNAF_WIDTH)?.to_affine())
+        } else {
+            Ok(pippenger(terms).to_affine())
+        }
+    }
+}
+
+/// Pippengerのバケット法
+///
+/// スカラーを c bit ずつの窓に分け、窓ごとに同じ値の点をバケットに集めてから
+/// Σ j * bucket[j] を累積和で求める
+fn pippenger<T>(terms: &[(Point<T, Integer>, Integer)]) -> JacobianPoint<T, Integer>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+{
+    let a = terms[0].0.a.clone();
+    let b = terms[0].0.b.clone();
+    let infinity = || JacobianPoint::infinity(a.clone(), b.clone());
+
+    // 負のスカラーは点を反転して扱う
+    let terms: Vec<_> = terms
+        .iter()
+        .map(|(point, scalar)| {
+            let mut base = JacobianPoint::from(point.clone());
+            if *scalar < 0 {
+                base.y = base.y.zero() - base.y;
+            }
+            (base, scalar.clone().abs())
+        })
+        .collect();
+
+    // 窓ごとのコストは 点の数 + 2^(c+1) (バケットの累積和) なので、
+    // 2^c が点の数の1/8程度になるよう log2(点の数) - 3 にする
+    let c = (usize::BITS - terms.len().leading_zeros())
+        .saturating_sub(3)
+        .max(2);
+    let bits = terms
+        .iter()
+        .map(|(_, scalar)| scalar.significant_bits())
+        .max()
+        .unwrap_or(0);
+    let windows = bits.div_ceil(c);
+
+    let mut result = infinity();
+    for window in (0..windows).rev() {
+        for _ in 0..c {
+            result = result.double();
+        }
+
+        let mut buckets = vec![infinity(); (1 << c) - 1];
+        for (base, scalar) in &terms {
+            let digit = (0..c).fold(0usize, |acc, i| {
+                acc | (usize::from(scalar.get_bit(window * c + i)) << i)
+            });
+            if digit > 0 {
+                buckets[digit - 1] = buckets[digit - 1].clone() + base.clone();
+            }
+        }
+
+        // Σ j * bucket[j] = bucket[max] + (bucket[max] + bucket[max-1]) + ...
+        let mut running = infinity();
+        let mut sum = infinity();
+        for bucket in buckets.into_iter().rev() {
+            running = running + bucket;
+            sum = sum + running.clone();
+        }
+        result = result + sum;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use field_element::FieldElement;
+    use rug::{ops::RemRounding, Integer};
+
+    use super::pippenger;
+    use crate::{Error, Point};
+
+    fn point(x: i32, y: i32) -> Point<FieldElement<Integer>, Integer> {
+        Point::new(
+            Some(FieldElement::new(Integer::from(x), Integer::from(223))),
+            Some(FieldElement::new(Integer::from(y), Integer::from(223))),
+            FieldElement::new(Integer::from(0), Integer::from(223)),
+            FieldElement::new(Integer::from(7), Integer::from(223)),
+        )
+    }
+
+    fn naive(
+        terms: &[(Point<FieldElement<Integer>, Integer>, Integer)],
+    ) -> Point<FieldElement<Integer>, Integer> {
+        // (47, 71) の倍数のみを使うので位数21で還元できる
+        terms
+            .iter()
+            .map(|(p, k)| p.clone() * k.clone().rem_euc(Integer::from(21)))
+            .reduce(|acc, p| acc + p)
+            .unwrap()
+    }
+
+    fn terms(count: i32) -> Vec<(Point<FieldElement<Integer>, Integer>, Integer)> {
+        let g = point(47, 71);
+        (0..count)
+            .map(|i| {
+                let p = g.clone() * Integer::from(i % 20 + 1);
+                let k = Integer::from(i * 7919 - 40000);
+                (p, k)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_scalar_mul() {
+        for count in [1, 2, 5, 31, 32, 40] {
+            let terms = terms(count);
+            assert_eq!(Point::multi_scalar_mul(&terms), Ok(naive(&terms)));
+            assert_eq!(pippenger(&terms).to_affine(), naive(&terms));
+        }
+
+        // P + (-P) は無限遠点
+        let g = point(47, 71);
+        let infinity = Point::new(None, None, g.a.clone(), g.b.clone());
+        let terms = [(g.clone(), Integer::from(5)), (g, Integer::from(-5))];
+        assert_eq!(Point::multi_scalar_mul(&terms), Ok(infinity.clone()));
+        assert_eq!(pippenger(&terms).to_affine(), infinity);
+
+        assert_eq!(
+            Point::<FieldElement<Integer>, Integer>::multi_scalar_mul(&[]),
+            Err(Error::EmptyTerms)
+        );
+    }
+}
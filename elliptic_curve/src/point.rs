@@ -4,8 +4,11 @@ use std::{
     ops::{Add, BitAnd, Div, Mul, ShrAssign, Sub},
 };
 
+use field_element::Field;
 use num_traits::Pow;
 
+use crate::{Error, JacobianPoint};
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Point<T, U> {
     pub x: Option<T>,
@@ -19,15 +22,15 @@ impl<T, U> Point<T, U>
 where
     T: Add<Output = T> + Mul<Output = T> + Pow<u32, Output = T> + PartialEq + Clone + Debug,
 {
-    pub fn new(x: Option<T>, y: Option<T>, a: T, b: T) -> Self {
+    pub fn try_new(x: Option<T>, y: Option<T>, a: T, b: T) -> Result<Self, Error> {
         if x.is_none() && y.is_none() {
-            return Self {
+            return Ok(Self {
                 x: None,
                 y: None,
                 a,
                 b,
                 _maker: PhantomData,
-            };
+            });
         }
 
         if x.is_none()
@@ -35,20 +38,27 @@ where
             || y.clone().unwrap().pow(2)
                 != x.clone().unwrap().pow(3) + a.clone() * x.clone().unwrap() + b.clone()
         {
-            panic!("({:?}, {:?}) is not on the curve", x, y);
+            return Err(Error::NotOnCurve {
+                x: format!("{:?}", x),
+                y: format!("{:?}", y),
+            });
         }
 
-        Self {
+        Ok(Self {
             x,
             y,
             a,
             b,
             _maker: PhantomData,
-        }
+        })
+    }
+
+    pub fn new(x: Option<T>, y: Option<T>, a: T, b: T) -> Self {
+        Self::try_new(x, y, a, b).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
-impl<T, U> Add for Point<T, U>
+impl<T, U> Point<T, U>
 where
     T: Add<Output = T>
         + Sub<Output = T>
@@ -61,19 +71,17 @@ where
         + Mul<i32, Output = T>,
     U: Debug + PartialEq,
 {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
+    pub fn checked_add(self, other: Self) -> Result<Self, Error> {
         if self.a != other.a || self.b != other.b {
-            panic!("Points {:?}, {:?} are not on the same curve", self, other);
+            return Err(Error::DifferentCurves);
         }
 
         // 加法単位元との加算
         if self.x.is_none() {
-            return other;
+            return Ok(other);
         }
         if other.x.is_none() {
-            return self;
+            return Ok(self);
         }
 
         let x1 = self.x.clone().unwrap();
@@ -83,13 +91,13 @@ where
 
         // 加法逆元との加算
         if x1 == x2 && y1 != y2 {
-            return Self {
+            return Ok(Self {
                 x: None,
                 y: None,
                 a: self.a,
                 b: self.b,
                 _maker: PhantomData,
-            };
+            });
         }
 
         // 異なる点の加算
@@ -97,18 +105,29 @@ where
             let s = (y2 - y1.clone()) / (x2.clone() - x1.clone());
             let x3 = s.clone().pow(2) - x1.clone() - x2;
             let y3 = s * (x1 - x3.clone()) - y1;
-            return Self::new(Some(x3), Some(y3), self.a, self.b);
+            return Self::try_new(Some(x3), Some(y3), self.a, self.b);
+        }
+
+        // 接線が垂直な場合 (2y = y すなわち y = 0) は無限遠点
+        if y1.clone() * 2 == y1 {
+            return Ok(Self {
+                x: None,
+                y: None,
+                a: self.a,
+                b: self.b,
+                _maker: PhantomData,
+            });
         }
 
         // 同じ点の加算
         let s = (x1.clone().pow(2) * 3 + self.a.clone()) / (y1.clone() * 2);
         let x3 = s.clone().pow(2) - x1.clone() * 2;
         let y3 = s * (x1 - x3.clone()) - y1;
-        Self::new(Some(x3), Some(y3), self.a, self.b)
+        Self::try_new(Some(x3), Some(y3), self.a, self.b)
     }
 }
 
-impl<T, U> Mul<U> for Point<T, U>
+impl<T, U> Add for Point<T, U>
 where
     T: Add<Output = T>
         + Sub<Output = T>
@@ -119,22 +138,34 @@ where
         + Clone
         + Debug
         + Mul<i32, Output = T>,
+    U: Debug + PartialEq,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<T, U> Mul<U> for Point<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
     U: Debug + Clone + PartialEq + PartialOrd + From<i32> + BitAnd<Output = U> + ShrAssign<i32>,
 {
     type Output = Self;
 
     fn mul(self, rhs: U) -> Self::Output {
-        let mut coef = rhs;
-        let mut current = self.clone();
-        let mut result = Point::new(None, None, self.a, self.b);
-        while coef > 0.into() {
-            if coef.clone() & 1.into() == 1.into() {
-                result = result + current.clone();
-            }
-            current = current.clone() + current;
-            coef >>= 1;
-        }
-        result
+        // ヤコビアン座標で計算し、最後に1回だけ逆元を求める
+        (JacobianPoint::from(self) * rhs).to_affine()
     }
 }
 
@@ -143,6 +174,7 @@ mod test {
     use rug::Integer;
 
     use super::Point;
+    use crate::Error;
     use field_element::FieldElement;
 
     #[test]
@@ -165,6 +197,36 @@ mod test {
         let _p4 = p1;
     }
 
+    #[test]
+    fn test_point_try_new() {
+        assert!(Point::<i32, i32>::try_new(Some(-1), Some(-1), 5, 7).is_ok());
+        assert_eq!(
+            Point::<i32, i32>::try_new(Some(-1), Some(-2), 5, 7),
+            Err(Error::NotOnCurve {
+                x: "Some(-1)".to_string(),
+                y: "Some(-2)".to_string()
+            })
+        );
+        assert!(Point::<i32, i32>::try_new(Some(-1), None, 5, 7).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "(Some(-1), Some(-2)) is not on the curve")]
+    fn test_point_new_panic() {
+        Point::<i32, i32>::new(Some(-1), Some(-2), 5, 7);
+    }
+
+    #[test]
+    fn test_point_checked_add() {
+        let p1: Point<i32, i32> = Point::new(Some(-1), Some(-1), 5, 7);
+        let p2: Point<i32, i32> = Point::new(Some(2), Some(5), 5, 7);
+        let p3: Point<i32, i32> = Point::new(Some(3), Some(-7), 5, 7);
+        let p4: Point<i32, i32> = Point::new(Some(0), Some(3), 0, 9);
+
+        assert_eq!(p1.clone().checked_add(p2), Ok(p3));
+        assert_eq!(p1.checked_add(p4), Err(Error::DifferentCurves));
+    }
+
     #[test]
     fn test_point_add() {
         let p1: Point<i32, i32> = Point::new(None, None, 5, 7);
@@ -0,0 +1,297 @@
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use field_element::Field;
+use num_traits::Pow;
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::Point;
+
+/// 射影座標 (X : Y : Z) で表した a = 0 の楕円曲線上の点
+///
+/// アフィン座標 (X / Z, Y / Z) に対応し、(0 : 1 : 0) が無限遠点を表す。
+/// 完全加算公式 (Renes-Costello-Batina 2016) を使うため、無限遠点や同じ点同士でも
+/// 場合分けなしに同じ手順で加算できる
+#[derive(Debug)]
+pub struct ProjectivePoint<T, U> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub a: T,
+    pub b: T,
+    _maker: PhantomData<fn() -> U>,
+}
+
+impl<T, U> ProjectivePoint<T, U>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field
+        + ConditionallySelectable,
+{
+    pub fn infinity(a: T, b: T) -> Self {
+        Self {
+            x: a.zero(),
+            y: a.one(),
+            z: a.zero(),
+            a,
+            b,
+            _maker: PhantomData,
+        }
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// アフィン座標に変換する (逆元を1回求める)
+    pub fn to_affine(&self) -> Point<T, U> {
+        if self.is_infinity() {
+            return Point::new(None, None, self.a, self.b);
+        }
+        let z_inv = self.z.one() / self.z;
+        Point::new(Some(self.x * z_inv), Some(self.y * z_inv), self.a, self.b)
+    }
+
+    /// 完全加算公式 (RCB16 Algorithm 7, a = 0)
+    pub fn add(&self, other: &Self) -> Self {
+        let b3 = self.b * 3;
+        let (x1, y1, z1) = (self.x, self.y, self.z);
+        let (x2, y2, z2) = (other.x, other.y, other.z);
+
+        let t0 = x1 * x2;
+        let t1 = y1 * y2;
+        let t2 = z1 * z2;
+        let t3 = (x1 + y1) * (x2 + y2) - (t0 + t1);
+        let t4 = (y1 + z1) * (y2 + z2) - (t1 + t2);
+        let y3 = (x1 + z1) * (x2 + z2) - (t0 + t2);
+        let t0 = t0 * 3;
+        let t2 = b3 * t2;
+        let z3 = t1 + t2;
+        let t1 = t1 - t2;
+        let y3 = b3 * y3;
+        let x3 = t3 * t1 - t4 * y3;
+        let y3 = t1 * z3 + y3 * t0;
+        let z3 = z3 * t4 + t0 * t3;
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+            a: self.a,
+            b: self.b,
+            _maker: PhantomData,
+        }
+    }
+
+    /// 完全二倍算公式 (RCB16 Algorithm 9, a = 0)
+    pub fn double(&self) -> Self {
+        let b3 = self.b * 3;
+        let (x, y, z) = (self.x, self.y, self.z);
+
+        let t0 = y * y;
+        let z3 = t0 * 8;
+        let t1 = y * z;
+        let t2 = b3 * z * z;
+        let x3 = t2 * z3;
+        let y3 = t0 + t2;
+        let z3 = t1 * z3;
+        let t0 = t0 - t2 * 3;
+        let y3 = x3 + t0 * y3;
+        let x3 = t0 * x * y * 2;
+
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+            a: self.a,
+            b: self.b,
+            _maker: PhantomData,
+        }
+    }
+
+    /// モンゴメリーラダーによるスカラー倍算
+    ///
+    /// スカラー (32バイトのビッグエンディアン) の値によらず、常に256回の加算・二倍算と
+    /// 条件付き交換を行うので、秘密鍵やnonceを掛けても処理時間から値が漏れない。
+    /// 完全加算公式は a = 0 の曲線でしか正しくないので、それ以外の曲線ではパニックする
+    pub fn mul_ct(&self, scalar: &[u8; 32]) -> Self {
+        assert!(self.a.is_zero(), "complete formulas require a = 0");
+
+        let mut r0 = Self::infinity(self.a, self.b);
+        let mut r1 = self.clone();
+        for byte in scalar {
+            for bit in (0..8).rev() {
+                let choice = Choice::from((byte >> bit) & 1);
+                Self::conditional_swap(&mut r0, &mut r1, choice);
+                r1 = r0.add(&r1);
+                r0 = r0.double();
+                Self::conditional_swap(&mut r0, &mut r1, choice);
+            }
+        }
+        r0
+    }
+
+    /// choice が1なら b、0なら a を分岐なしで選ぶ
+    pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: T::conditional_select(&a.x, &b.x, choice),
+            y: T::conditional_select(&a.y, &b.y, choice),
+            z: T::conditional_select(&a.z, &b.z, choice),
+            a: a.a,
+            b: a.b,
+            _maker: PhantomData,
+        }
+    }
+
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        T::conditional_swap(&mut a.x, &mut b.x, choice);
+        T::conditional_swap(&mut a.y, &mut b.y, choice);
+        T::conditional_swap(&mut a.z, &mut b.z, choice);
+    }
+}
+
+// derive(Clone) だと U: Clone が要求されるため手動で実装する
+impl<T: Clone, U> Clone for ProjectivePoint<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            x: self.x.clone(),
+            y: self.y.clone(),
+            z: self.z.clone(),
+            a: self.a.clone(),
+            b: self.b.clone(),
+            _maker: PhantomData,
+        }
+    }
+}
+
+impl<T, U> From<Point<T, U>> for ProjectivePoint<T, U>
+where
+    T: Field,
+{
+    fn from(point: Point<T, U>) -> Self {
+        match (point.x, point.y) {
+            (Some(x), Some(y)) => Self {
+                z: x.one(),
+                x,
+                y,
+                a: point.a,
+                b: point.b,
+                _maker: PhantomData,
+            },
+            _ => Self {
+                x: point.a.zero(),
+                y: point.a.one(),
+                z: point.a.zero(),
+                a: point.a,
+                b: point.b,
+                _maker: PhantomData,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use field_element::Fe256;
+    use rug::{integer::Order, rand::RandState, Integer};
+
+    use super::ProjectivePoint;
+    use crate::Point;
+
+    fn generator() -> Point<Fe256, Integer> {
+        let gx = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let gy = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+        Point::new(
+            Some(Fe256::from_bytes(&hex::decode(gx).unwrap().try_into().unwrap()).unwrap()),
+            Some(Fe256::from_bytes(&hex::decode(gy).unwrap().try_into().unwrap()).unwrap()),
+            Fe256::ZERO,
+            Fe256::from_u64(7),
+        )
+    }
+
+    fn infinity() -> Point<Fe256, Integer> {
+        Point::new(None, None, Fe256::ZERO, Fe256::from_u64(7))
+    }
+
+    fn to_bytes(scalar: &Integer) -> [u8; 32] {
+        let digits = scalar.to_digits::<u8>(Order::MsfBe);
+        let mut bytes = [0u8; 32];
+        bytes[32 - digits.len()..].copy_from_slice(&digits);
+        bytes
+    }
+
+    #[test]
+    fn test_projective_add() {
+        let g = generator();
+        let pg = ProjectivePoint::from(g.clone());
+        let pi = ProjectivePoint::from(infinity());
+
+        assert_eq!(pg.to_affine(), g);
+        assert_eq!(pi.to_affine(), infinity());
+
+        // 同じ点・無限遠点・逆元も同じ公式で扱える
+        let g2 = g.clone() + g.clone();
+        assert_eq!(pg.add(&pg).to_affine(), g2);
+        assert_eq!(pg.double().to_affine(), g2);
+        assert_eq!(pg.add(&pi).to_affine(), g);
+        assert_eq!(pi.add(&pg).to_affine(), g);
+        assert!(pi.double().is_infinity());
+        assert!(pi.add(&pi).is_infinity());
+
+        let neg_g = Point::new(g.x, Some(-g.y.unwrap()), g.a, g.b);
+        assert!(pg.add(&ProjectivePoint::from(neg_g)).is_infinity());
+
+        // Zが1でない点同士の加算
+        let g3 = pg.double().add(&pg);
+        assert_eq!(g3.add(&pg.double()).to_affine(), g2.clone() + g2 + g);
+    }
+
+    #[test]
+    fn test_projective_mul_ct() {
+        let g = generator();
+        let pg = ProjectivePoint::from(g.clone());
+        let n = Integer::from_str_radix(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            16,
+        )
+        .unwrap();
+
+        let mut expected = infinity();
+        for k in 0..8 {
+            assert_eq!(
+                pg.mul_ct(&to_bytes(&Integer::from(k))).to_affine(),
+                expected
+            );
+            expected = expected + g.clone();
+        }
+
+        let mut rand = RandState::new();
+        let mut scalars = vec![n.clone() - 1, n.clone() - 2];
+        for _ in 0..8 {
+            scalars.push(Integer::from(n.random_below_ref(&mut rand)));
+        }
+        for k in scalars {
+            assert_eq!(pg.mul_ct(&to_bytes(&k)).to_affine(), g.clone() * k.clone());
+        }
+        assert!(pg.mul_ct(&to_bytes(&n)).is_infinity());
+    }
+
+    #[test]
+    #[should_panic(expected = "complete formulas require a = 0")]
+    fn test_projective_mul_ct_requires_a_zero() {
+        let point =
+            Point::<Fe256, Integer>::new(None, None, Fe256::from_u64(1), Fe256::from_u64(7));
+        ProjectivePoint::from(point).mul_ct(&[1; 32]);
+    }
+}
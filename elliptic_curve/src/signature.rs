@@ -1,5 +1,7 @@
 use rug::{integer::Order, Integer};
 
+use crate::Error;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Signature<T> {
     pub r: T,
@@ -38,6 +40,56 @@ impl Signature<Integer> {
 
         der
     }
+
+    /// BIP66 の strict DER ルールに従って署名をパースする
+    pub fn parse_der(der: &[u8]) -> Result<Self, Error> {
+        // 0x30 [total-length] 0x02 [R-length] [R] 0x02 [S-length] [S]
+        if der.len() < 8 {
+            return Err(Error::InvalidDer("too short"));
+        }
+        if der.len() > 72 {
+            return Err(Error::InvalidDer("too long"));
+        }
+        if der[0] != 0x30 {
+            return Err(Error::InvalidDer("missing compound marker"));
+        }
+        if der[1] as usize != der.len() - 2 {
+            return Err(Error::InvalidDer("length mismatch"));
+        }
+
+        let r_len = der[3] as usize;
+        if 5 + r_len >= der.len() {
+            return Err(Error::InvalidDer("r length overflows signature"));
+        }
+        let s_len = der[5 + r_len] as usize;
+        if r_len + s_len + 6 != der.len() {
+            return Err(Error::InvalidDer("length mismatch"));
+        }
+
+        let r = Signature::parse_der_integer(&der[2..4 + r_len])?;
+        let s = Signature::parse_der_integer(&der[4 + r_len..])?;
+
+        Ok(Self { r, s })
+    }
+
+    fn parse_der_integer(bytes: &[u8]) -> Result<Integer, Error> {
+        if bytes[0] != 0x02 {
+            return Err(Error::InvalidDer("missing integer marker"));
+        }
+        let value = &bytes[2..];
+        if value.is_empty() {
+            return Err(Error::InvalidDer("zero-length integer"));
+        }
+        // 負の整数は不可
+        if value[0] & 0x80 != 0 {
+            return Err(Error::InvalidDer("negative integer"));
+        }
+        // 必要以上の0x00パディングは不可
+        if value.len() > 1 && value[0] == 0x00 && value[1] & 0x80 == 0 {
+            return Err(Error::InvalidDer("excess padding"));
+        }
+        Ok(Integer::from_digits(value, Order::MsfBe))
+    }
 }
 
 #[cfg(test)]
@@ -45,7 +97,7 @@ mod test {
     use hex::ToHex;
     use rug::Integer;
 
-    use crate::Signature;
+    use crate::{Error, Signature};
 
     #[test]
     fn test_der() {
@@ -64,4 +116,95 @@ mod test {
 
         assert_eq!(signature.der().encode_hex::<String>(), "3045022037206a0610995c58074999cb9767b87af4c4978db68c06e8e6e81d282047a7c60221008ca63759c1157ebeaec0d03cecca119fc9a75bf8e6d0fa65c841c8e2738cdaec");
     }
+
+    #[test]
+    fn test_parse_der() {
+        let der = hex::decode("3045022037206a0610995c58074999cb9767b87af4c4978db68c06e8e6e81d282047a7c60221008ca63759c1157ebeaec0d03cecca119fc9a75bf8e6d0fa65c841c8e2738cdaec").unwrap();
+        let signature = Signature::parse_der(&der).unwrap();
+
+        assert_eq!(
+            signature.r,
+            Integer::from_str_radix(
+                "37206a0610995c58074999cb9767b87af4c4978db68c06e8e6e81d282047a7c6",
+                16,
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            signature.s,
+            Integer::from_str_radix(
+                "8ca63759c1157ebeaec0d03cecca119fc9a75bf8e6d0fa65c841c8e2738cdaec",
+                16,
+            )
+            .unwrap()
+        );
+        assert_eq!(signature.der(), der);
+
+        let short = Signature::new(Integer::from(1), Integer::from(0x7f));
+        assert_eq!(Signature::parse_der(&short.der()).unwrap(), short);
+    }
+
+    #[test]
+    fn test_parse_der_strict() {
+        let parse = |s: &str| Signature::parse_der(&hex::decode(s).unwrap());
+
+        assert_eq!(
+            parse("3006020101020101"),
+            Ok(Signature::new(Integer::from(1), Integer::from(1)))
+        );
+        assert_eq!(parse("30060201010201"), Err(Error::InvalidDer("too short")));
+        assert_eq!(
+            parse("3106020101020101"),
+            Err(Error::InvalidDer("missing compound marker"))
+        );
+        assert_eq!(
+            parse("3007020101020101"),
+            Err(Error::InvalidDer("length mismatch"))
+        );
+        // 末尾の余分なバイト
+        assert_eq!(
+            parse("300602010102010100"),
+            Err(Error::InvalidDer("length mismatch"))
+        );
+        assert_eq!(
+            parse("3006020601020101"),
+            Err(Error::InvalidDer("r length overflows signature"))
+        );
+        assert_eq!(
+            parse("3006020101020201"),
+            Err(Error::InvalidDer("length mismatch"))
+        );
+        assert_eq!(
+            parse("3006030101020101"),
+            Err(Error::InvalidDer("missing integer marker"))
+        );
+        assert_eq!(
+            parse("3006020101030101"),
+            Err(Error::InvalidDer("missing integer marker"))
+        );
+        assert_eq!(
+            parse("3006020002020101"),
+            Err(Error::InvalidDer("zero-length integer"))
+        );
+        assert_eq!(
+            parse("3006020181020101"),
+            Err(Error::InvalidDer("negative integer"))
+        );
+        assert_eq!(
+            parse("3006020101020181"),
+            Err(Error::InvalidDer("negative integer"))
+        );
+        assert_eq!(
+            parse("300702020001020101"),
+            Err(Error::InvalidDer("excess padding"))
+        );
+        assert_eq!(
+            parse("300702010102020001"),
+            Err(Error::InvalidDer("excess padding"))
+        );
+        assert_eq!(
+            parse("3007020101020200ff"),
+            Ok(Signature::new(Integer::from(1), Integer::from(0xff)))
+        );
+    }
 }
@@ -0,0 +1,183 @@
+use std::{
+    fmt::Debug,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use field_element::Field;
+use num_traits::Pow;
+use rug::Integer;
+
+use crate::{Error, JacobianPoint, Point};
+
+/// スカラーを幅 `width` のwNAF (最下位から順) に変換する
+///
+/// 各桁は0か絶対値が 2^(width-1) 未満の奇数で、0でない桁の間には必ず width-1 個以上の0が入る
+pub fn wnaf(scalar: &Integer, width: u32) -> Vec<i8> {
+    assert!((2..=8).contains(&width), "wNAF width must be in 2..=8");
+    assert!(*scalar >= 0, "wNAF scalar must be non-negative");
+
+    let modulus = 1i32 << width;
+    let mut k = scalar.clone();
+    let mut digits = vec![];
+    while k > 0 {
+        let mut digit = 0;
+        if k.is_odd() {
+            digit = k.mod_u(modulus as u32) as i32;
+            if digit >= modulus / 2 {
+                digit -= modulus;
+            }
+            k -= digit;
+        }
+        digits.push(digit as i8);
+        k >>= 1;
+    }
+    digits
+}
+
+/// Σ scalars[i] * points[i] を、各スカラーのwNAFで二倍算を共有しながら計算する
+///
+/// 負のスカラーは点を反転して扱う。公開データ向けの可変時間の計算なので、
+/// 秘密のスカラーには使わないこと。点が1つもなければ `Error::EmptyTerms` を返す
+pub fn multi_mul_wnaf<T, U>(
+    terms: &[(Point<T, U>, Integer)],
+    width: u32,
+) -> Result<JacobianPoint<T, U>, Error>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+    U: Clone,
+{
+    let (a, b) = match terms.first() {
+        Some((point, _)) => (point.a.clone(), point.b.clone()),
+        None => return Err(Error::EmptyTerms),
+    };
+
+    let mut tables = vec![];
+    let mut digits = vec![];
+    for (point, scalar) in terms {
+        let mut base = JacobianPoint::from(point.clone());
+        if *scalar < 0 {
+            base = negate(&base);
+        }
+        tables.push(odd_multiples(&base, width));
+        digits.push(wnaf(&scalar.clone().abs(), width));
+    }
+
+    let len = digits.iter().map(|d| d.len()).max().unwrap_or(0);
+    let mut result = JacobianPoint::infinity(a, b);
+    for i in (0..len).rev() {
+        result = result.double();
+        for (table, digit) in tables.iter().zip(&digits) {
+            match digit.get(i) {
+                Some(&d) if d > 0 => result = result + table[(d / 2) as usize].clone(),
+                Some(&d) if d < 0 => result = result + negate(&table[(-d / 2) as usize]),
+                _ => {}
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// P, 3P, 5P, ..., (2^(width-1) - 1)P
+fn odd_multiples<T, U>(base: &JacobianPoint<T, U>, width: u32) -> Vec<JacobianPoint<T, U>>
+where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Pow<u32, Output = T>
+        + PartialEq
+        + Clone
+        + Debug
+        + Mul<i32, Output = T>
+        + Field,
+{
+    let twice = base.double();
+    let mut table = vec![base.clone()];
+    for i in 1..(1 << (width - 2)) {
+        let next = table[i - 1].clone() + twice.clone();
+        table.push(next);
+    }
+    table
+}
+
+fn negate<T, U>(point: &JacobianPoint<T, U>) -> JacobianPoint<T, U>
+where
+    T: Sub<Output = T> + Clone + Field,
+{
+    let mut negated = point.clone();
+    negated.y = point.y.zero() - point.y.clone();
+    negated
+}
+
+#[cfg(test)]
+mod test {
+    use field_element::FieldElement;
+    use rug::{rand::RandState, Integer};
+
+    use super::{multi_mul_wnaf, wnaf};
+    use crate::{Error, Point};
+
+    fn point(x: i32, y: i32) -> Point<FieldElement<Integer>, Integer> {
+        Point::new(
+            Some(FieldElement::new(Integer::from(x), Integer::from(223))),
+            Some(FieldElement::new(Integer::from(y), Integer::from(223))),
+            FieldElement::new(Integer::from(0), Integer::from(223)),
+            FieldElement::new(Integer::from(7), Integer::from(223)),
+        )
+    }
+
+    #[test]
+    fn test_wnaf() {
+        assert_eq!(wnaf(&Integer::from(0), 4), Vec::<i8>::new());
+        assert_eq!(wnaf(&Integer::from(7), 2), vec![-1, 0, 0, 1]);
+
+        let mut rand = RandState::new();
+        let bound = Integer::from(1) << 256u32;
+        for width in 2..=8 {
+            for _ in 0..16 {
+                let k = Integer::from(bound.random_below_ref(&mut rand));
+                let digits = wnaf(&k, width);
+
+                let mut value = Integer::new();
+                for (i, &d) in digits.iter().enumerate() {
+                    value += Integer::from(d) << i as u32;
+                    if d != 0 {
+                        assert!(d % 2 != 0 && (d.unsigned_abs() as u32) < 1 << (width - 1));
+                        let next = &digits[i + 1..digits.len().min(i + width as usize)];
+                        assert!(next.iter().all(|&d| d == 0));
+                    }
+                }
+                assert_eq!(value, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_mul_wnaf() {
+        // (47, 71) の位数は21、(15, 86) の位数は7
+        let p1 = point(47, 71);
+        let p2 = point(15, 86);
+        for k1 in -21i32..=21 {
+            for k2 in [-13i32, 0, 5] {
+                let expected = p1.clone() * Integer::from(k1.rem_euclid(21))
+                    + p2.clone() * Integer::from(k2.rem_euclid(7));
+                let terms = [
+                    (p1.clone(), Integer::from(k1)),
+                    (p2.clone(), Integer::from(k2)),
+                ];
+                assert_eq!(multi_mul_wnaf(&terms, 3).unwrap().to_affine(), expected);
+            }
+        }
+        let empty: [(Point<FieldElement<Integer>, Integer>, Integer); 0] = [];
+        assert_eq!(multi_mul_wnaf(&empty, 3).err(), Some(Error::EmptyTerms));
+    }
+}
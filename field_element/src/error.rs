@@ -0,0 +1,27 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// `num` が `0..prime` の範囲外
+    OutOfRange { num: String, max: String },
+    /// 異なる有限体の元同士の演算
+    DifferentFields { op: &'static str },
+    /// ゼロ除算
+    DivisionByZero,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange { num, max } => {
+                write!(f, "Num {} not in field range 0 to {}", num, max)
+            }
+            Error::DifferentFields { op } => {
+                write!(f, "Cannot {} two numbers in different Fields", op)
+            }
+            Error::DivisionByZero => write!(f, "Cannot divide by zero"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
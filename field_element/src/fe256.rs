@@ -0,0 +1,471 @@
+use std::{
+    fmt::{self, Debug},
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use num_traits::Pow;
+use rug::{integer::Order, Integer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use crate::{Error, FieldElement};
+
+/// secp256k1 の素数 p = 2^256 - 2^32 - 977 (リトルエンディアンの64bitリム)
+const P: [u64; 4] = [
+    0xFFFF_FFFE_FFFF_FC2F,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// 2^256 mod p = 2^32 + 977
+const C: u64 = 0x1_0000_03D1;
+
+/// p - 2 (逆元の指数)
+const P_MINUS_2: [u64; 4] = [
+    0xFFFF_FFFE_FFFF_FC2D,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// (p + 1) / 4 (平方根の指数)
+const P_PLUS_1_DIV_4: [u64; 4] = [
+    0xFFFF_FFFF_BFFF_FF0C,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0x3FFF_FFFF_FFFF_FFFF,
+];
+
+/// secp256k1 の有限体 F_p の元を 4x64bit のリムで表したもの
+///
+/// 常に `0..p` に正規化されているので、リムの比較がそのまま値の比較になる
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct Fe256([u64; 4]);
+
+impl Fe256 {
+    pub const ZERO: Fe256 = Fe256([0, 0, 0, 0]);
+    pub const ONE: Fe256 = Fe256([1, 0, 0, 0]);
+
+    pub fn from_u64(num: u64) -> Self {
+        Fe256([num, 0, 0, 0])
+    }
+
+    /// 32バイトのビッグエンディアンから変換する
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, Error> {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+        }
+        if !less_than(&limbs, &P) {
+            return Err(Error::OutOfRange {
+                num: format!("{:?}", Integer::from_digits(bytes, Order::MsfBe)),
+                max: format!("{:?}", Integer::from_digits(&P, Order::Lsf) - 1),
+            });
+        }
+        Ok(Fe256(limbs))
+    }
+
+    /// 32バイトのビッグエンディアンに変換する
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn try_from_integer(num: &Integer) -> Result<Self, Error> {
+        if *num < 0 || num.significant_bits() > 256 {
+            return Err(Error::OutOfRange {
+                num: format!("{:?}", num),
+                max: format!("{:?}", Integer::from_digits(&P, Order::Lsf) - 1),
+            });
+        }
+        let digits = num.to_digits::<u8>(Order::MsfBe);
+        let mut bytes = [0u8; 32];
+        bytes[32 - digits.len()..].copy_from_slice(&digits);
+        Fe256::from_bytes(&bytes)
+    }
+
+    pub fn to_integer(&self) -> Integer {
+        Integer::from_digits(&self.0, Order::Lsf)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0, 0, 0, 0]
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.0[0] & 1 == 1
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// フェルマーの小定理により self^(p-2) を逆元として返す (0の逆元は0)
+    pub fn invert(&self) -> Self {
+        self.pow_limbs(&P_MINUS_2)
+    }
+
+    /// p ≡ 3 (mod 4) なので self^((p+1)/4) が平方根の候補になる
+    pub fn sqrt(&self) -> Option<Self> {
+        let root = self.pow_limbs(&P_PLUS_1_DIV_4);
+        if root.square() == *self {
+            Some(root)
+        } else {
+            None
+        }
+    }
+
+    pub fn checked_div(self, other: Self) -> Result<Self, Error> {
+        if other.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(self * other.invert())
+    }
+
+    /// 指数 `exp` (リトルエンディアンの64bitリム) による累乗
+    fn pow_limbs(&self, exp: &[u64; 4]) -> Self {
+        // 繰り返し二乗法
+        let mut ret = Fe256::ONE;
+        for limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                ret = ret.square();
+                if (limb >> bit) & 1 == 1 {
+                    ret = ret * *self;
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// a < b
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    let (_, borrow) = sub_limbs(a, b);
+    borrow == 1
+}
+
+/// 256bitの加算 (繰り上がりを返す)
+pub(crate) fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut r = [0u64; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let acc = a[i] as u128 + b[i] as u128 + carry;
+        r[i] = acc as u64;
+        carry = acc >> 64;
+    }
+    (r, carry as u64)
+}
+
+/// 256bitの減算 (繰り下がりを返す)
+pub(crate) fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut r = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(b[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        r[i] = d2;
+        borrow = (b1 | b2) as u64;
+    }
+    (r, borrow)
+}
+
+/// mask が全bit1なら a、0なら b を選ぶ
+pub(crate) fn select(mask: u64, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut r = [0u64; 4];
+    for i in 0..4 {
+        r[i] = (a[i] & mask) | (b[i] & !mask);
+    }
+    r
+}
+
+/// 2^256 未満の値を p で1回だけ還元する
+fn reduce_once(r: [u64; 4], carry: u64) -> [u64; 4] {
+    // r + 2^256 * carry >= p ⇔ r + C が 2^256 を超える or carry
+    let (t, c) = add_limbs(&r, &[C, 0, 0, 0]);
+    let mask = 0u64.wrapping_sub(c | carry);
+    select(mask, &t, &r)
+}
+
+impl Add for Fe256 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (r, carry) = add_limbs(&self.0, &other.0);
+        Fe256(reduce_once(r, carry))
+    }
+}
+
+impl Sub for Fe256 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        // 繰り下がった場合は p を足す (2^256 を法として C を引くのと同じ)
+        let (r, borrow) = sub_limbs(&self.0, &other.0);
+        let (t, _) = sub_limbs(&r, &[C, 0, 0, 0]);
+        let mask = 0u64.wrapping_sub(borrow);
+        Fe256(select(mask, &t, &r))
+    }
+}
+
+impl Neg for Fe256 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Fe256::ZERO - self
+    }
+}
+
+impl Mul for Fe256 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let a = &self.0;
+        let b = &other.0;
+
+        // 512bitの積
+        let mut t = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let acc = t[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+                t[i + j] = acc as u64;
+                carry = acc >> 64;
+            }
+            t[i + 4] = carry as u64;
+        }
+
+        // 2^256 ≡ C を使って上位256bitを畳み込む
+        let mut r = [0u64; 4];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let acc = t[i] as u128 + t[i + 4] as u128 * C as u128 + carry;
+            r[i] = acc as u64;
+            carry = acc >> 64;
+        }
+
+        // 溢れた分 (34bit以下) をもう一度畳み込む
+        let mut carry = carry * C as u128;
+        for limb in r.iter_mut() {
+            let acc = *limb as u128 + carry;
+            *limb = acc as u64;
+            carry = acc >> 64;
+        }
+
+        Fe256(reduce_once(r, carry as u64))
+    }
+}
+
+impl Div for Fe256 {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self::Output {
+        self.checked_div(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Mul<i32> for Fe256 {
+    type Output = Self;
+
+    fn mul(self, other: i32) -> Self::Output {
+        let n = Fe256::from_u64(other.unsigned_abs() as u64);
+        if other < 0 {
+            -(self * n)
+        } else {
+            self * n
+        }
+    }
+}
+
+impl Pow<u32> for Fe256 {
+    type Output = Self;
+
+    fn pow(self, exp: u32) -> Self::Output {
+        // 繰り返し二乗法 (指数の最上位bitから)
+        let mut ret = Fe256::ONE;
+        for bit in (0..(32 - exp.leading_zeros())).rev() {
+            ret = ret.square();
+            if (exp >> bit) & 1 == 1 {
+                ret = ret * self;
+            }
+        }
+        ret
+    }
+}
+
+// 秘密の値に依存する分岐を避けるため、選択・比較はリム単位のビット演算で行う
+impl ConditionallySelectable for Fe256 {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut r = [0u64; 4];
+        for (i, limb) in r.iter_mut().enumerate() {
+            *limb = u64::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        Fe256(r)
+    }
+}
+
+impl ConstantTimeEq for Fe256 {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl Debug for Fe256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fe256({:#x})", self.to_integer())
+    }
+}
+
+impl From<Fe256> for FieldElement<Integer> {
+    fn from(fe: Fe256) -> Self {
+        FieldElement {
+            num: fe.to_integer(),
+            prime: Integer::from_digits(&P, Order::Lsf),
+        }
+    }
+}
+
+impl TryFrom<FieldElement<Integer>> for Fe256 {
+    type Error = Error;
+
+    fn try_from(fe: FieldElement<Integer>) -> Result<Self, Self::Error> {
+        if fe.prime != Integer::from_digits(&P, Order::Lsf) {
+            return Err(Error::DifferentFields { op: "convert" });
+        }
+        Fe256::try_from_integer(&fe.num)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_traits::Pow;
+    use rug::{rand::RandState, Integer};
+    use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+    use super::{Fe256, P};
+    use crate::{pow::PowMod, Error, FieldElement};
+
+    fn prime() -> Integer {
+        Integer::from_digits(&P, rug::integer::Order::Lsf)
+    }
+
+    fn samples() -> Vec<Integer> {
+        let p = prime();
+        let mut rand = RandState::new();
+        let mut samples = vec![
+            Integer::from(0),
+            Integer::from(1),
+            Integer::from(2),
+            p.clone() - 1,
+            p.clone() - 2,
+            Integer::from(1) << 255,
+            Integer::from(u64::MAX),
+            (Integer::from(1) << 256) - p.clone() - 1,
+        ];
+        for _ in 0..32 {
+            samples.push(Integer::from(p.random_below_ref(&mut rand)));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_fe256_conversion() {
+        let p = prime();
+        for num in samples() {
+            let fe = Fe256::try_from_integer(&num).unwrap();
+            assert_eq!(fe.to_integer(), num);
+            assert_eq!(Fe256::from_bytes(&fe.to_bytes()).unwrap(), fe);
+
+            let field_element = FieldElement::new(num, p.clone());
+            assert_eq!(Fe256::try_from(field_element.clone()).unwrap(), fe);
+            assert_eq!(FieldElement::from(fe), field_element);
+        }
+
+        assert!(matches!(
+            Fe256::try_from_integer(&p),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(Fe256::try_from_integer(&Integer::from(-1)).is_err());
+        assert!(Fe256::from_bytes(&[0xff; 32]).is_err());
+        assert_eq!(
+            Fe256::try_from(FieldElement::new(Integer::from(1), Integer::from(13))),
+            Err(Error::DifferentFields { op: "convert" })
+        );
+    }
+
+    #[test]
+    fn test_fe256_arithmetic() {
+        let p = prime();
+        let samples = samples();
+        for a in &samples {
+            for b in samples.iter().step_by(3) {
+                let fa = Fe256::try_from_integer(a).unwrap();
+                let fb = Fe256::try_from_integer(b).unwrap();
+                let ea = FieldElement::new(a.clone(), p.clone());
+                let eb = FieldElement::new(b.clone(), p.clone());
+
+                assert_eq!((fa + fb).to_integer(), (ea.clone() + eb.clone()).num);
+                assert_eq!((fa - fb).to_integer(), (ea.clone() - eb.clone()).num);
+                assert_eq!((fa * fb).to_integer(), (ea.clone() * eb.clone()).num);
+                if !fb.is_zero() {
+                    assert_eq!((fa / fb).to_integer(), (ea / eb).num);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fe256_pow() {
+        let p = prime();
+        for a in samples() {
+            let fa = Fe256::try_from_integer(&a).unwrap();
+            let ea = FieldElement::new(a.clone(), p.clone());
+
+            assert_eq!(
+                fa.pow(3).to_integer(),
+                ea.pow_mod(Integer::from(3), p.clone()).num
+            );
+            assert_eq!((fa * 3).to_integer(), (ea.clone() * 3).num);
+            assert_eq!((fa * -1).to_integer(), (-fa).to_integer());
+            assert_eq!((fa + -fa), Fe256::ZERO);
+            if !fa.is_zero() {
+                assert_eq!(fa * fa.invert(), Fe256::ONE);
+            }
+
+            let square = fa.square();
+            let root = square.sqrt().unwrap();
+            assert!(root == fa || root == -fa);
+        }
+
+        // -1 は平方非剰余
+        assert_eq!((-Fe256::ONE).sqrt(), None);
+    }
+
+    #[test]
+    fn test_fe256_constant_time() {
+        let samples = samples();
+        for (a, b) in samples.iter().zip(samples.iter().skip(1)) {
+            let mut fa = Fe256::try_from_integer(a).unwrap();
+            let mut fb = Fe256::try_from_integer(b).unwrap();
+
+            assert_eq!(Fe256::conditional_select(&fa, &fb, Choice::from(0)), fa);
+            assert_eq!(Fe256::conditional_select(&fa, &fb, Choice::from(1)), fb);
+            assert!(bool::from(fa.ct_eq(&fa)));
+            assert_eq!(bool::from(fa.ct_eq(&fb)), fa == fb);
+
+            Fe256::conditional_swap(&mut fa, &mut fb, Choice::from(1));
+            assert_eq!(fa.to_integer(), *b);
+            assert_eq!(fb.to_integer(), *a);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot divide by zero")]
+    fn test_fe256_div_by_zero() {
+        let _ = Fe256::ONE / Fe256::ZERO;
+    }
+}
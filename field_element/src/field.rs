@@ -0,0 +1,49 @@
+use std::ops::Rem;
+
+use crate::{Fe256, FieldElement};
+
+/// 有限体の加法単位元・乗法単位元
+///
+/// `FieldElement` は元ごとに位数を持つので、同じ体の単位元を `self` から作る
+pub trait Field {
+    fn zero(&self) -> Self;
+    fn one(&self) -> Self;
+    fn is_zero(&self) -> bool;
+}
+
+impl<T> Field for FieldElement<T>
+where
+    T: From<i32> + Clone + PartialEq + Rem<Output = T>,
+{
+    fn zero(&self) -> Self {
+        Self {
+            num: 0.into(),
+            prime: self.prime.clone(),
+        }
+    }
+
+    fn one(&self) -> Self {
+        Self {
+            num: T::from(1) % self.prime.clone(),
+            prime: self.prime.clone(),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num == 0.into()
+    }
+}
+
+impl Field for Fe256 {
+    fn zero(&self) -> Self {
+        Fe256::ZERO
+    }
+
+    fn one(&self) -> Self {
+        Fe256::ONE
+    }
+
+    fn is_zero(&self) -> bool {
+        Fe256::is_zero(self)
+    }
+}
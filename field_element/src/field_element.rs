@@ -5,7 +5,7 @@ use std::{
 
 use num_traits::Pow;
 
-use crate::pow::PowMod;
+use crate::{pow::PowMod, Error};
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct FieldElement<T> {
@@ -15,17 +15,20 @@ pub struct FieldElement<T> {
 
 impl<T> FieldElement<T>
 where
-    T: PartialOrd + Debug + Sub<Output = T> + From<i32>,
+    T: PartialOrd + Debug + Sub<Output = T> + From<i32> + Clone,
 {
-    pub fn new(num: T, prime: T) -> Self {
-        if num >= prime {
-            panic!(
-                "Num {:?} not in field range 0 to {:?}",
-                num,
-                prime - 1.into()
-            );
+    pub fn try_new(num: T, prime: T) -> Result<Self, Error> {
+        if num < 0.into() || num >= prime {
+            return Err(Error::OutOfRange {
+                num: format!("{:?}", num),
+                max: format!("{:?}", prime - 1.into()),
+            });
         }
-        Self { num, prime }
+        Ok(Self { num, prime })
+    }
+
+    pub fn new(num: T, prime: T) -> Self {
+        Self::try_new(num, prime).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -87,32 +90,28 @@ where
     }
 }
 
-impl<T> Add for FieldElement<T>
+impl<T> FieldElement<T>
 where
     T: Add<Output = T> + Clone + PartialEq + Rem<Output = T>,
 {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
+    pub fn checked_add(self, other: Self) -> Result<Self, Error> {
         if self.prime != other.prime {
-            panic!("Cannot add two numbers in different Fields");
+            return Err(Error::DifferentFields { op: "add" });
         }
-        Self {
+        Ok(Self {
             num: (self.num + other.num) % self.prime.clone(),
             prime: self.prime,
-        }
+        })
     }
 }
 
-impl<T> Sub for FieldElement<T>
+impl<T> FieldElement<T>
 where
     T: PartialEq + PartialOrd + Sub<Output = T> + Clone + Add<Output = T>,
 {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self::Output {
+    pub fn checked_sub(self, other: Self) -> Result<Self, Error> {
         if self.prime != other.prime {
-            panic!("Cannot subtract two numbers in different Fields");
+            return Err(Error::DifferentFields { op: "subtract" });
         }
 
         let num = if self.num >= other.num {
@@ -121,27 +120,84 @@ where
             self.prime.clone() - other.num + self.num
         };
 
-        Self {
+        Ok(Self {
             num,
             prime: self.prime,
-        }
+        })
     }
 }
 
-impl<T> Mul for FieldElement<T>
+impl<T> FieldElement<T>
 where
     T: PartialEq + Mul<Output = T> + Clone + Rem<Output = T>,
 {
-    type Output = Self;
-
-    fn mul(self, other: Self) -> Self::Output {
+    pub fn checked_mul(self, other: Self) -> Result<Self, Error> {
         if self.prime != other.prime {
-            panic!("Cannot multiply two numbers in different Fields");
+            return Err(Error::DifferentFields { op: "multiply" });
         }
-        Self {
+        Ok(Self {
             num: (self.num * other.num) % self.prime.clone(),
             prime: self.prime,
+        })
+    }
+}
+
+impl<T> FieldElement<T>
+where
+    T: PartialEq
+        + PartialOrd
+        + Sub<Output = T>
+        + From<i32>
+        + Clone
+        + Mul<Output = T>
+        + BitAnd<Output = T>
+        + Rem<Output = T>
+        + ShrAssign<i32>
+        + Add<Output = T>,
+{
+    pub fn checked_div(self, other: Self) -> Result<Self, Error> {
+        if self.prime != other.prime {
+            return Err(Error::DifferentFields { op: "divide" });
+        }
+        if other.num == 0.into() {
+            return Err(Error::DivisionByZero);
         }
+        // フェルマーの小定理により other^(p-2) が逆元
+        self.clone()
+            .checked_mul(other.pow_mod(self.prime.clone() - 2.into(), self.prime))
+    }
+}
+
+impl<T> Add for FieldElement<T>
+where
+    T: Add<Output = T> + Clone + PartialEq + Rem<Output = T>,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<T> Sub for FieldElement<T>
+where
+    T: PartialEq + PartialOrd + Sub<Output = T> + Clone + Add<Output = T>,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self.checked_sub(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<T> Mul for FieldElement<T>
+where
+    T: PartialEq + Mul<Output = T> + Clone + Rem<Output = T>,
+{
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        self.checked_mul(other).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -161,10 +217,7 @@ where
     type Output = Self;
 
     fn div(self, other: Self) -> Self::Output {
-        if self.prime != other.prime {
-            panic!("Cannot divide two numbers in different Fields");
-        }
-        self.clone() * other.pow_mod(self.prime.clone() - 2.into(), self.prime)
+        self.checked_div(other).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -182,7 +235,7 @@ where
 #[cfg(test)]
 mod test {
 
-    use crate::pow::PowMod;
+    use crate::{pow::PowMod, Error};
 
     use super::FieldElement;
     use rug::Integer;
@@ -279,4 +332,48 @@ mod test {
         assert_eq!(a / b, c);
         assert_eq!(d / e, f);
     }
+
+    #[test]
+    fn test_field_element_try_new() {
+        assert!(FieldElement::try_new(Integer::from(12), Integer::from(13)).is_ok());
+        assert_eq!(
+            FieldElement::try_new(Integer::from(13), Integer::from(13)),
+            Err(Error::OutOfRange {
+                num: "13".to_string(),
+                max: "12".to_string()
+            })
+        );
+        assert!(FieldElement::try_new(-1, 13).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Num 13 not in field range 0 to 12")]
+    fn test_field_element_new_panic() {
+        FieldElement::new(13, 13);
+    }
+
+    #[test]
+    fn test_field_element_checked() {
+        let a = FieldElement::new(Integer::from(3), Integer::from(31));
+        let b = FieldElement::new(Integer::from(3), Integer::from(13));
+        let zero = FieldElement::new(Integer::from(0), Integer::from(31));
+
+        assert_eq!(
+            a.clone().checked_add(b.clone()),
+            Err(Error::DifferentFields { op: "add" })
+        );
+        assert_eq!(
+            a.clone().checked_sub(b.clone()),
+            Err(Error::DifferentFields { op: "subtract" })
+        );
+        assert_eq!(
+            a.clone().checked_mul(b.clone()),
+            Err(Error::DifferentFields { op: "multiply" })
+        );
+        assert_eq!(
+            a.clone().checked_div(b),
+            Err(Error::DifferentFields { op: "divide" })
+        );
+        assert_eq!(a.checked_div(zero), Err(Error::DivisionByZero));
+    }
 }
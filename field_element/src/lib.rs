@@ -1,3 +1,11 @@
+mod error;
+mod fe256;
+mod field;
 mod field_element;
 pub mod pow;
+mod scalar;
+pub use crate::error::Error;
+pub use crate::fe256::Fe256;
+pub use crate::field::Field;
 pub use crate::field_element::FieldElement;
+pub use crate::scalar::Scalar;
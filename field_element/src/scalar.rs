@@ -0,0 +1,401 @@
+use std::{
+    fmt::{self, Debug},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+use zeroize::Zeroize;
+
+use crate::fe256::{add_limbs, select, sub_limbs};
+
+/// secp256k1 の位数 n (リトルエンディアンの64bitリム)
+const N: [u64; 4] = [
+    0xBFD2_5E8C_D036_4141,
+    0xBAAE_DCE6_AF48_A03B,
+    0xFFFF_FFFF_FFFF_FFFE,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// 2^256 mod n = 2^256 - n (129bit)
+const NC: [u64; 4] = [0x402D_A173_2FC9_BEBF, 0x4551_2319_50B7_5FC4, 1, 0];
+
+/// n - 2 (逆元の指数)
+const N_MINUS_2: [u64; 4] = [
+    0xBFD2_5E8C_D036_413F,
+    0xBAAE_DCE6_AF48_A03B,
+    0xFFFF_FFFF_FFFF_FFFE,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// (n - 1) / 2
+const N_HALF: [u64; 4] = [
+    0xDFE9_2F46_681B_20A0,
+    0x5D57_6E73_57A4_501D,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0x7FFF_FFFF_FFFF_FFFF,
+];
+
+/// secp256k1 の位数 n を法とするスカラーを 4x64bit のリムで表したもの
+///
+/// 秘密鍵やnonceの計算に使うため、すべての演算は値によらず同じ手順で行い、
+/// 比較も `subtle` の `Choice` で返す。常に `0..n` に正規化されている。
+/// 秘密の値が暗黙に複製されないよう `Copy` にはせず、演算は参照でも行えるようにしている
+#[derive(Clone, Default)]
+pub struct Scalar([u64; 4]);
+
+impl Scalar {
+    pub const ZERO: Scalar = Scalar([0, 0, 0, 0]);
+    pub const ONE: Scalar = Scalar([1, 0, 0, 0]);
+
+    pub fn from_u64(num: u64) -> Self {
+        Scalar([num, 0, 0, 0])
+    }
+
+    /// 32バイトのビッグエンディアンから変換する。n 以上なら None
+    pub fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        let limbs = limbs_from_bytes(bytes);
+        let (_, borrow) = sub_limbs(&limbs, &N);
+        CtOption::new(Scalar(limbs), Choice::from(borrow as u8))
+    }
+
+    /// 32バイトのビッグエンディアンを n で還元して変換する
+    pub fn from_bytes_reduced(bytes: &[u8; 32]) -> Self {
+        Scalar(reduce_once(limbs_from_bytes(bytes), 0))
+    }
+
+    /// 32バイトのビッグエンディアンに変換する
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> Choice {
+        self.ct_eq(&Scalar::ZERO)
+    }
+
+    /// n/2 より大きいか (low-S の正規化に使う)
+    pub fn is_high(&self) -> Choice {
+        let (_, borrow) = sub_limbs(&N_HALF, &self.0);
+        Choice::from(borrow as u8)
+    }
+
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    /// フェルマーの小定理により self^(n-2) を逆元として返す (0の逆元は0)
+    ///
+    /// 指数は公開の定数なので、二乗と乗算の順序は self によらない
+    pub fn invert(&self) -> Self {
+        let mut ret = Scalar::ONE;
+        for limb in N_MINUS_2.iter().rev() {
+            for bit in (0..64).rev() {
+                ret = ret.square();
+                if (limb >> bit) & 1 == 1 {
+                    ret = &ret * self;
+                }
+            }
+        }
+        ret
+    }
+
+    /// choice が1なら b、0なら a を分岐せずに選ぶ
+    ///
+    /// `ConditionallySelectable` は `Copy` を要求するので、同じ処理を固有メソッドで持つ
+    pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut r = [0u64; 4];
+        for (i, limb) in r.iter_mut().enumerate() {
+            *limb = u64::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+        Scalar(r)
+    }
+}
+
+fn limbs_from_bytes(bytes: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 32 - (i + 1) * 8;
+        *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+    }
+    limbs
+}
+
+/// r + 2^256 * carry (< 2n) を n で1回だけ還元する
+fn reduce_once(r: [u64; 4], carry: u64) -> [u64; 4] {
+    let (t, borrow) = sub_limbs(&r, &N);
+    // 繰り上がりがあるか、引いても繰り下がらなければ n 以上
+    let mask = 0u64.wrapping_sub(carry | (borrow ^ 1));
+    select(mask, &t, &r)
+}
+
+/// 2^256 ≡ NC (mod n) を使って、上位256bitを NC 倍して下位に足し込む
+fn fold(t: &[u64; 8]) -> [u64; 8] {
+    let mut r = [0u64; 8];
+    r[..4].copy_from_slice(&t[..4]);
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let acc = r[i + j] as u128 + t[i + 4] as u128 * NC[j] as u128 + carry;
+            r[i + j] = acc as u64;
+            carry = acc >> 64;
+        }
+        for limb in r.iter_mut().skip(i + 4) {
+            let acc = *limb as u128 + carry;
+            *limb = acc as u64;
+            carry = acc >> 64;
+        }
+    }
+    r
+}
+
+impl Add<&Scalar> for &Scalar {
+    type Output = Scalar;
+
+    fn add(self, other: &Scalar) -> Scalar {
+        let (r, carry) = add_limbs(&self.0, &other.0);
+        Scalar(reduce_once(r, carry))
+    }
+}
+
+impl Sub<&Scalar> for &Scalar {
+    type Output = Scalar;
+
+    fn sub(self, other: &Scalar) -> Scalar {
+        // 繰り下がった場合は n を足す
+        let (r, borrow) = sub_limbs(&self.0, &other.0);
+        let (t, _) = add_limbs(&r, &N);
+        Scalar(select(0u64.wrapping_sub(borrow), &t, &r))
+    }
+}
+
+impl Neg for &Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        &Scalar::ZERO - self
+    }
+}
+
+impl Mul<&Scalar> for &Scalar {
+    type Output = Scalar;
+
+    fn mul(self, other: &Scalar) -> Scalar {
+        let a = &self.0;
+        let b = &other.0;
+
+        // 512bitの積
+        let mut t = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let acc = t[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+                t[i + j] = acc as u64;
+                carry = acc >> 64;
+            }
+            t[i + 4] = carry as u64;
+        }
+
+        // 畳み込むたびに上位が 512 → 386 → 260 → 257bit 以下と縮み、4回目で256bitに収まる。
+        // 値によらず常に4回行う
+        for _ in 0..4 {
+            t = fold(&t);
+        }
+        Scalar(reduce_once([t[0], t[1], t[2], t[3]], 0))
+    }
+}
+
+// 値を受け取る演算は参照の演算に委ねる
+impl Add<&Scalar> for Scalar {
+    type Output = Scalar;
+
+    fn add(self, other: &Scalar) -> Scalar {
+        &self + other
+    }
+}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    fn add(self, other: Scalar) -> Scalar {
+        &self + &other
+    }
+}
+
+impl Sub<&Scalar> for Scalar {
+    type Output = Scalar;
+
+    fn sub(self, other: &Scalar) -> Scalar {
+        &self - other
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Scalar;
+
+    fn sub(self, other: Scalar) -> Scalar {
+        &self - &other
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        -&self
+    }
+}
+
+impl Mul<&Scalar> for Scalar {
+    type Output = Scalar;
+
+    fn mul(self, other: &Scalar) -> Scalar {
+        &self * other
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Scalar;
+
+    fn mul(self, other: Scalar) -> Scalar {
+        &self * &other
+    }
+}
+
+impl ConstantTimeEq for Scalar {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for Scalar {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for Scalar {}
+
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+// 秘密の値を保持するので、ログなどに出力されないよう伏せる
+impl Debug for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Scalar(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rug::{integer::Order, rand::RandState, Integer};
+    use subtle::Choice;
+
+    use super::{Scalar, N, NC, N_HALF, N_MINUS_2};
+
+    fn order() -> Integer {
+        Integer::from_digits(&N, Order::Lsf)
+    }
+
+    fn to_integer(scalar: &Scalar) -> Integer {
+        Integer::from_digits(&scalar.to_bytes(), Order::MsfBe)
+    }
+
+    fn to_bytes(num: &Integer) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        num.write_digits(&mut bytes, Order::MsfBe);
+        bytes
+    }
+
+    fn samples() -> Vec<Integer> {
+        let n = order();
+        let mut rand = RandState::new();
+        let mut samples = vec![
+            Integer::from(0),
+            Integer::from(1),
+            Integer::from(2),
+            n.clone() - 1,
+            n.clone() - 2,
+            n.clone() / 2,
+            n.clone() / 2 + 1,
+            Integer::from(u64::MAX),
+        ];
+        for _ in 0..32 {
+            samples.push(Integer::from(n.random_below_ref(&mut rand)));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_scalar_constants() {
+        let n = order();
+        let two_256 = Integer::from(1) << 256;
+        assert_eq!(Integer::from_digits(&NC, Order::Lsf), two_256 - &n);
+        assert_eq!(Integer::from_digits(&N_MINUS_2, Order::Lsf), n.clone() - 2);
+        assert_eq!(Integer::from_digits(&N_HALF, Order::Lsf), (n - 1) / 2);
+    }
+
+    #[test]
+    fn test_scalar_conversion() {
+        let n = order();
+        for num in samples() {
+            let scalar = Option::<Scalar>::from(Scalar::from_bytes(&to_bytes(&num))).unwrap();
+            assert_eq!(to_integer(&scalar), num);
+            assert_eq!(Scalar::from_bytes_reduced(&to_bytes(&num)), scalar);
+        }
+
+        // n 以上の値は from_bytes では拒否し、from_bytes_reduced では還元する
+        for num in [n.clone(), n.clone() + 1, (Integer::from(1) << 256) - 1] {
+            assert!(bool::from(Scalar::from_bytes(&to_bytes(&num)).is_none()));
+            assert_eq!(
+                to_integer(&Scalar::from_bytes_reduced(&to_bytes(&num))),
+                num - &n
+            );
+        }
+    }
+
+    #[test]
+    fn test_scalar_arithmetic() {
+        let n = order();
+        let samples = samples();
+        for a in &samples {
+            let sa = Scalar::from_bytes_reduced(&to_bytes(a));
+            for b in &samples {
+                let sb = Scalar::from_bytes_reduced(&to_bytes(b));
+                assert_eq!(to_integer(&(&sa + &sb)), (a.clone() + b) % &n);
+                assert_eq!(to_integer(&(&sa - &sb)), (a.clone() - b + &n) % &n);
+                assert_eq!(to_integer(&(&sa * &sb)), a.clone() * b % &n);
+                // 値を受け取る演算も同じ結果になる
+                assert_eq!(sa.clone() * sb.clone() + &sb, &(&sa * &sb) + &sb);
+            }
+            assert_eq!(to_integer(&-&sa), (n.clone() - a) % &n);
+            assert_eq!(-sa.clone(), -&sa);
+
+            if *a != 0 {
+                assert_eq!(sa.invert() * &sa, Scalar::ONE);
+            }
+            assert_eq!(bool::from(sa.is_high()), *a > n.clone() / 2);
+            assert_eq!(bool::from(sa.is_zero()), *a == 0);
+        }
+        assert_eq!(Scalar::ZERO.invert(), Scalar::ZERO);
+
+        // 最大の積 (n-1)^2 も還元できる
+        let max = Scalar::from_bytes_reduced(&to_bytes(&(n.clone() - 1)));
+        assert_eq!(&max * &max, Scalar::ONE);
+    }
+
+    #[test]
+    fn test_scalar_select() {
+        let a = Scalar::from_u64(3);
+        let b = Scalar::from_u64(5);
+        assert_eq!(Scalar::conditional_select(&a, &b, Choice::from(0)), a);
+        assert_eq!(Scalar::conditional_select(&a, &b, Choice::from(1)), b);
+        assert_eq!(format!("{:?}", a), "Scalar(<redacted>)");
+    }
+}